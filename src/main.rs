@@ -2,7 +2,10 @@ use std::borrow::Cow;
 use std::fs::create_dir_all;
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
 
+use reflection::Lake;
 use skia_safe::{Canvas, EncodedImageFormat, Paint, Surface};
 mod geometry;
 mod hitomezashi;
@@ -11,6 +14,8 @@ mod mountain;
 mod mugen;
 mod night;
 mod rabbit;
+mod reflection;
+mod terminal;
 mod tree;
 mod utils;
 
@@ -23,9 +28,37 @@ struct Painting<'a> {
 }
 
 fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        None => draw_all(),
+        Some("heightmap") => draw_heightmap(&args[1..]),
+        Some("mugen") => draw_mugen(&args[1..]),
+        Some("maze") => draw_maze(&args[1..]),
+        Some("maze-load") => load_maze(&args[1..]),
+        Some("maze-book") => draw_maze_book(&args[1..]),
+        Some("maze-animation") => draw_maze_animation(&args[1..]),
+        Some("maze-play") => play_maze(&args[1..]),
+        Some(command) => Err(format!("ERROR: unknown command `{}`", command)),
+    }
+}
+
+fn draw_all() -> Result<(), String> {
     let paintings = vec![
         Painting::new(tree::draw, "tree"),
+        Painting::new(
+            |canvas| Lake::default().draw_reflected(canvas, tree::draw),
+            "tree_lake",
+        ),
         Painting::new(mountain::draw, "mountain"),
+        Painting::new(mountain::draw_dusk, "mountain_dusk"),
+        Painting::new(mountain::draw_night, "mountain_night"),
+        Painting::new(
+            |canvas| Lake::default().draw_reflected(canvas, mountain::draw),
+            "mountain_lake",
+        ),
+        Painting::new(mountain::draw_range, "mountain_range"),
+        Painting::new(mountain::draw_hazy_range, "mountain_haze"),
         Painting::new(maze::draw, "maze"),
         Painting::new(mugen::draw, "mugen"),
         Painting::new(night::draw, "night"),
@@ -37,9 +70,602 @@ fn main() -> Result<(), String> {
         draw(painting.draw_fn, painting.output, (WIDTH, HEIGHT))?;
     }
 
+    export_tree_skeleton()?;
+
+    Ok(())
+}
+
+/// Draws the mountains of a grayscale heightmap image or a CSV elevation profile:
+/// `heightmap <file> [rows...]`. Without rows, three rows are evenly picked from the map.
+fn draw_heightmap(args: &[String]) -> Result<(), String> {
+    let file_name = args
+        .first()
+        .ok_or_else(|| "ERROR: usage: heightmap <file> [rows...]".to_string())?;
+    println!("===\nDrawing mountain_heightmap from {}", file_name);
+
+    let content = std::fs::read(file_name)
+        .map_err(|_e| format!("ERROR: failed to read the file `{}`", file_name))?;
+
+    let heightmap = if file_name.ends_with(".csv") {
+        mountain::Heightmap::from_csv(&String::from_utf8_lossy(&content))?
+    } else {
+        mountain::Heightmap::from_image(&content)?
+    };
+
+    let rows = args[1..]
+        .iter()
+        .map(|row| {
+            let row = row
+                .parse::<usize>()
+                .map_err(|_e| format!("ERROR: `{}` is not a row number", row))?;
+            if row >= heightmap.height() {
+                return Err(format!(
+                    "ERROR: row {} is outside the heightmap (height {})",
+                    row,
+                    heightmap.height()
+                ));
+            }
+            Ok(row)
+        })
+        .collect::<Result<Vec<usize>, String>>()?;
+    let rows = if !rows.is_empty() {
+        rows
+    } else if heightmap.height() < 3 {
+        (0..heightmap.height()).collect()
+    } else {
+        heightmap.spread_rows(3)
+    };
+
+    let mut surface =
+        Surface::new_raster_n32_premul((WIDTH, HEIGHT)).expect("No SKIA surface available.");
+    mountain::draw_heightmap(surface.canvas(), &heightmap, &rows);
+
+    save(&mut surface, "mountain_heightmap")?;
+
+    println!("===\n");
+
     Ok(())
 }
 
+/// Draws a parametric curve in the style of the mugen painting:
+/// `mugen <curve> [--<parameter> <value>...]`, the parameters of each curve being listed when an
+/// unknown one is given.
+fn draw_mugen(args: &[String]) -> Result<(), String> {
+    let name = args
+        .first()
+        .ok_or_else(|| "ERROR: usage: mugen <curve> [--<parameter> <value>...]".to_string())?;
+    let mut curve: mugen::Curve = name.parse()?;
+    for (parameter, value) in parse_options(&args[1..])? {
+        let value = value
+            .parse()
+            .map_err(|_e| format!("ERROR: `{}` is not a number", value))?;
+        curve.set(parameter, value)?;
+    }
+
+    let output = format!("mugen_{}", curve);
+    let parameters: Vec<String> = curve
+        .parameters()
+        .iter()
+        .map(|(name, value)| format!("{} {}", name, value))
+        .collect();
+    println!("===\nDrawing {} ({})", output, parameters.join(", "));
+
+    let mut surface =
+        Surface::new_raster_n32_premul((WIDTH, HEIGHT)).expect("No SKIA surface available.");
+    mugen::draw_curve(surface.canvas(), &curve);
+
+    save(&mut surface, &output)?;
+
+    println!("===\n");
+
+    Ok(())
+}
+
+/// Options of the commands generating new mazes:
+/// `[--shape <name>] [--generator <name>] [--solver <name>] [--braid <ratio>] [--seed <seed>]`,
+/// square mazes following the shape of `[--mask-image <png file>]`, `[--mask-path <svg path>]` or
+/// `[--mask-text <text>]`. The entry and the exits of square mazes are on a side or inside, with
+/// `[--entry <door>]` and `[--exits <door>,<door>...]`, or at the ends of the longest path with
+/// `[--placement longest-path]`. Passages of square mazes go under each other with
+/// `[--weave true]`, and lead to the opposite side with `[--wrap <none|horizontal|vertical|both>]`,
+/// the entry and the exit being at the ends of the longest path when the west and east sides
+/// wrap. Square mazes get several levels joined by stairs with `[--levels <count>]`,
+/// drawn `[--projection <side-by-side|isometric>]`, and have `[--cells <width>x<height>]` cells
+/// instead of as many as fit the picture. Their walls are drawn in a style, see `parse_style`.
+/// `[--difficulty <score>]` searches, from the seed, for a maze of that difficulty from 0 to 100,
+/// trying up to `[--tries <count>]` seeds.
+struct MazeArgs<'a> {
+    options: maze::MazeOptions,
+    /// The target difficulty, and how many seeds to try.
+    difficulty: Option<(f32, u64)>,
+    /// Options specific to each command.
+    others: Vec<(&'a str, &'a str)>,
+}
+
+fn parse_maze_options(args: &[String]) -> Result<MazeArgs<'_>, String> {
+    let mut options = maze::MazeOptions::default();
+    let mut difficulty = None;
+    let mut tries = 200;
+    let mut entry = None;
+    let mut exits = None;
+    let mut longest_path = false;
+    let mut walls = None;
+    let mut corridor_ratio = None;
+    let mut others = Vec::new();
+    for (name, value) in parse_options(args)? {
+        match name {
+            "shape" => options.shape = value.parse()?,
+            "mask-image" => {
+                let content = std::fs::read(value)
+                    .map_err(|_e| format!("ERROR: failed to read the file `{}`", value))?;
+                options.mask = Some(maze::Mask::from_png(&content)?);
+            }
+            "mask-path" => options.mask = Some(maze::Mask::from_svg_path(value)?),
+            "mask-text" => options.mask = Some(maze::Mask::from_text(value)?),
+            "generator" => options.generator = value.parse()?,
+            "solver" => options.solver = value.parse()?,
+            "braid" => {
+                options.braid = value
+                    .parse()
+                    .ok()
+                    .filter(|braid| (0.0..=1.0).contains(braid))
+                    .ok_or_else(|| {
+                        format!(
+                            "ERROR: the braid must be between 0.0 and 1.0, not `{}`",
+                            value
+                        )
+                    })?
+            }
+            "weave" => {
+                options.weave = value
+                    .parse()
+                    .map_err(|_e| format!("ERROR: `{}` is not true or false", value))?
+            }
+            "wrap" => options.wrap = value.parse()?,
+            "levels" => {
+                options.levels = value
+                    .parse()
+                    .ok()
+                    .filter(|levels| *levels > 0)
+                    .ok_or_else(|| format!("ERROR: `{}` is not a valid number of levels", value))?
+            }
+            "projection" => options.projection = value.parse()?,
+            "cells" => options.cells = Some(parse_size(value)?),
+            "walls" => walls = Some(value),
+            "corridor-ratio" => corridor_ratio = Some(value),
+            "seed" => {
+                options.seed = value
+                    .parse()
+                    .map_err(|_e| format!("ERROR: `{}` is not a valid seed", value))?
+            }
+            "difficulty" => {
+                difficulty = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|difficulty| (0.0..=100.0).contains(difficulty))
+                        .ok_or_else(|| {
+                            format!(
+                                "ERROR: the difficulty must be between 0 and 100, not `{}`",
+                                value
+                            )
+                        })?,
+                )
+            }
+            "entry" => entry = Some(value.parse()?),
+            "exits" => {
+                exits = Some(
+                    value
+                        .split(',')
+                        .map(str::parse)
+                        .collect::<Result<Vec<maze::Door>, String>>()?,
+                )
+            }
+            "placement" => {
+                longest_path = match value {
+                    "doors" => false,
+                    "longest-path" => true,
+                    _ => {
+                        return Err(format!(
+                            "ERROR: unknown placement `{}`, expected one of doors, longest-path",
+                            value
+                        ))
+                    }
+                }
+            }
+            "tries" => {
+                tries = value
+                    .parse()
+                    .ok()
+                    .filter(|tries| *tries > 0)
+                    .ok_or_else(|| format!("ERROR: `{}` is not a valid number of tries", value))?
+            }
+            _ => others.push((name, value)),
+        }
+    }
+
+    options.placement = match (longest_path, entry, exits) {
+        (true, None, None) => maze::Placement::LongestPath,
+        (true, _, _) => {
+            return Err("ERROR: the longest path places the entry and the exit itself".to_string())
+        }
+        // The sides of the default doors lead to the opposite side.
+        (false, None, None) if options.wrap.horizontal() => maze::Placement::LongestPath,
+        (false, entry, exits) => maze::Placement::Doors {
+            entry: entry.unwrap_or(maze::Door::West),
+            exits: exits.unwrap_or_else(|| vec![maze::Door::East]),
+        },
+    };
+
+    options.style = parse_style(walls, corridor_ratio)?;
+
+    Ok(MazeArgs {
+        options,
+        difficulty: difficulty.map(|difficulty| (difficulty, tries)),
+        others,
+    })
+}
+
+/// Draws a maze with custom options, see `MazeArgs`, on a picture of `[--size <width>x<height>]`.
+/// Square mazes can be saved to a `[--save <file>]`, as JSON if it ends with `.json`, as ASCII art
+/// otherwise.
+fn draw_maze(args: &[String]) -> Result<(), String> {
+    let MazeArgs {
+        mut options,
+        difficulty,
+        others,
+    } = parse_maze_options(args)?;
+    let mut save_file = None;
+    let mut size = (WIDTH, HEIGHT);
+    for (name, value) in others {
+        match name {
+            "save" => save_file = Some(value),
+            "size" => size = parse_size(value)?,
+            _ => return Err(format!("ERROR: unknown option `--{}`", name)),
+        }
+    }
+
+    let mut surface = Surface::new_raster_n32_premul(size).expect("No SKIA surface available.");
+    if let Some((difficulty, tries)) = difficulty {
+        options.seed = maze::find_seed(surface.canvas(), &options, difficulty, tries)?;
+    }
+
+    let output = match options.shape {
+        maze::Shape::Square if options.levels > 1 => format!(
+            "maze_{}_levels_{}_{}",
+            options.levels, options.generator, options.seed
+        ),
+        maze::Shape::Square => format!("maze_{}_{}", options.generator, options.seed),
+        shape => format!("maze_{}_{}_{}", shape, options.generator, options.seed),
+    };
+    println!("===\nDrawing {}", output);
+
+    let maze = maze::draw_with_options(surface.canvas(), &options)?;
+
+    if let Some(file_name) = save_file {
+        let maze = maze
+            .ok_or_else(|| "ERROR: only square mazes of a single level can be saved".to_string())?;
+        let content = if file_name.ends_with(".json") {
+            maze.to_json()?
+        } else if options.wrap != maze::Wrap::None {
+            return Err("ERROR: wrapped mazes can only be saved as JSON".to_string());
+        } else {
+            maze.to_ascii()
+        };
+        write_file(file_name, content.as_bytes())?;
+    }
+
+    save(&mut surface, &output)?;
+
+    println!("===\n");
+
+    Ok(())
+}
+
+/// Draws a puzzle book of square mazes, see `MazeArgs`, on `[--pages <count>]` pages of
+/// `[--paper <a4|letter>]`, followed by pages of answers.
+fn draw_maze_book(args: &[String]) -> Result<(), String> {
+    let MazeArgs {
+        options,
+        difficulty,
+        others,
+    } = parse_maze_options(args)?;
+    let mut pages = 10;
+    let mut paper = maze::Paper::A4;
+    for (name, value) in others {
+        match name {
+            "pages" => {
+                pages = value
+                    .parse()
+                    .ok()
+                    .filter(|pages| *pages > 0)
+                    .ok_or_else(|| format!("ERROR: `{}` is not a valid number of pages", value))?
+            }
+            "paper" => paper = value.parse()?,
+            _ => return Err(format!("ERROR: unknown option `--{}`", name)),
+        }
+    }
+
+    let output = format!("maze_book_{}", options.generator);
+    println!("===\nDrawing {} on {} paper", output, paper);
+
+    let book = maze::Book::new(paper, pages, &options, difficulty)?;
+    let mut surface =
+        Surface::new_raster_n32_premul(book.paper().pixels()).expect("No SKIA surface available.");
+    for page in 1..=book.page_count() {
+        book.draw_page(surface.canvas(), page)?;
+        save(&mut surface, &format!("{}_page_{:02}", output, page))?;
+    }
+
+    println!("===\n");
+
+    Ok(())
+}
+
+/// Draws the frames of the animation of a square maze, see `MazeArgs`, showing
+/// `[--animate <generation|solution|both>]` over `[--frames <count>]` pictures of
+/// `[--size <width>x<height>]`.
+fn draw_maze_animation(args: &[String]) -> Result<(), String> {
+    let MazeArgs {
+        mut options,
+        difficulty,
+        others,
+    } = parse_maze_options(args)?;
+    let mut animation = maze::Animation::default();
+    let mut frames = 100;
+    let mut size = (WIDTH, HEIGHT);
+    for (name, value) in others {
+        match name {
+            "animate" => animation = value.parse()?,
+            "frames" => {
+                frames = value
+                    .parse()
+                    .ok()
+                    .filter(|frames| *frames > 0)
+                    .ok_or_else(|| format!("ERROR: `{}` is not a valid number of frames", value))?
+            }
+            "size" => size = parse_size(value)?,
+            _ => return Err(format!("ERROR: unknown option `--{}`", name)),
+        }
+    }
+
+    let mut surface = Surface::new_raster_n32_premul(size).expect("No SKIA surface available.");
+    if let Some((difficulty, tries)) = difficulty {
+        options.seed = maze::find_seed(surface.canvas(), &options, difficulty, tries)?;
+    }
+
+    let output = format!(
+        "maze_{}_{}_{}_{}",
+        animation,
+        options.generator,
+        options.effective_solver(),
+        options.seed
+    );
+    println!("===\nDrawing {} in {} frames", output, frames);
+
+    let maze_animation = maze::MazeAnimation::fit(surface.canvas(), &options, animation)?;
+    println!("{}", maze_animation.maze());
+    for frame in 0..frames {
+        maze_animation.draw_frame(surface.canvas(), frame, frames);
+        save(&mut surface, &format!("{}_frame_{:04}", output, frame))?;
+    }
+
+    println!("===\n");
+
+    Ok(())
+}
+
+/// Plays a square maze, see `MazeArgs`, in the terminal: the arrow keys walk it, `s` shows or
+/// hides the solution and `q` gives up. The maze fits the terminal, unless given its `--cells`.
+fn play_maze(args: &[String]) -> Result<(), String> {
+    let MazeArgs {
+        options,
+        difficulty,
+        others,
+    } = parse_maze_options(args)?;
+    if let Some((name, _)) = others.first() {
+        return Err(format!("ERROR: unknown option `--{}`", name));
+    }
+    if difficulty.is_some() {
+        return Err("ERROR: only drawn mazes can be searched for a difficulty".to_string());
+    }
+    if options.shape != maze::Shape::Square || options.levels > 1 {
+        return Err("ERROR: only square mazes of a single level can be played".to_string());
+    }
+
+    let terminal = terminal::RawTerminal::new()?;
+    let (columns, rows) = terminal.size()?;
+    // Two characters per cell of the full grid across, and two lines for the status below.
+    let (width, height) = options
+        .cells
+        .unwrap_or((columns.saturating_sub(1) / 4, rows.saturating_sub(3) / 2));
+    if width == 0 || height == 0 {
+        return Err("ERROR: the terminal is too small for a maze".to_string());
+    }
+    let mut player = maze::Player::new(maze::Maze::new(width, height, &options)?)?;
+
+    // Playing on the alternate screen, without the cursor, to leave the terminal as it was.
+    print!("\x1b[?1049h\x1b[?25l\x1b[2J");
+    let start = Instant::now();
+    let result = loop {
+        print!(
+            "\x1b[H{}\r\nMoves: {}  Time: {}s  [arrows] walk  [s] solution  [q] give up",
+            player.render().replace('\n', "\r\n"),
+            player.moves(),
+            start.elapsed().as_secs()
+        );
+        if let Err(e) = std::io::stdout().flush() {
+            break Err(format!("ERROR: failed to draw the maze: {}", e));
+        }
+        if player.is_out() {
+            break Ok(true);
+        }
+
+        let step = match terminal.read_key() {
+            Ok(Some(terminal::Key::Up)) => maze::Move::Up,
+            Ok(Some(terminal::Key::Down)) => maze::Move::Down,
+            Ok(Some(terminal::Key::Left)) => maze::Move::Left,
+            Ok(Some(terminal::Key::Right)) => maze::Move::Right,
+            Ok(Some(terminal::Key::Char('s'))) => {
+                player.toggle_solution();
+                continue;
+            }
+            // Raw mode turns Ctrl-C into a key.
+            Ok(Some(terminal::Key::Char('q' | '\u{3}'))) => break Ok(false),
+            Ok(_) => continue,
+            Err(e) => break Err(e),
+        };
+        player.step(step);
+    };
+    print!("\x1b[?25h\x1b[?1049l");
+    drop(terminal);
+
+    match result? {
+        true => println!(
+            "Out in {} moves and {}s!",
+            player.moves(),
+            start.elapsed().as_secs()
+        ),
+        false => println!("Gave up after {} moves", player.moves()),
+    }
+
+    Ok(())
+}
+
+/// Draws a maze saved by `maze --save`: `maze-load <file> [--solver <name>] [--size <width>x<height>]`,
+/// in a style, see `parse_style`. The saved solution is kept, unless another solver is picked.
+/// Mazes with doors that aren't open, or a solution going through walls, are rejected.
+fn load_maze(args: &[String]) -> Result<(), String> {
+    let file_name = args.first().ok_or_else(|| {
+        "ERROR: usage: maze-load <file> [--solver <name>] [--size <size>] [--walls <style>]"
+            .to_string()
+    })?;
+
+    let content = std::fs::read_to_string(file_name)
+        .map_err(|_e| format!("ERROR: failed to read the file `{}`", file_name))?;
+    let mut maze = if file_name.ends_with(".json") {
+        maze::Maze::from_json(&content)?
+    } else {
+        maze::Maze::from_ascii(&content)?
+    };
+
+    let mut size = (WIDTH, HEIGHT);
+    let mut solver = None;
+    let mut walls = None;
+    let mut corridor_ratio = None;
+    for (name, value) in parse_options(&args[1..])? {
+        match name {
+            "solver" => solver = Some(value.parse()?),
+            "size" => size = parse_size(value)?,
+            "walls" => walls = Some(value),
+            "corridor-ratio" => corridor_ratio = Some(value),
+            _ => return Err(format!("ERROR: unknown option `--{}`", name)),
+        }
+    }
+    let style = parse_style(walls, corridor_ratio)?;
+
+    // Loaded mazes might have loops, where a depth-first search would not find the shortest path.
+    if solver.is_some() || maze.solution().is_none() {
+        maze = maze.solve(solver.unwrap_or(maze::Solver::BreadthFirst));
+    }
+    // Saved files might have been edited since.
+    maze.validate()?;
+
+    let stem = Path::new(file_name)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("loaded");
+    let output = format!("maze_{}", stem);
+    println!("===\nDrawing {} from {}", output, file_name);
+    println!("{}", maze);
+    match maze.solution() {
+        Some(solution) => println!("Solution: {}", solution),
+        None => println!("No solution found"),
+    }
+    println!("{}", maze.statistics());
+    match maze.check_perfect() {
+        Ok(()) => println!("A perfect maze, with a single path between any two cells"),
+        Err(_) => println!("Not a perfect maze, with loops or cells out of reach"),
+    }
+
+    let mut surface = Surface::new_raster_n32_premul(size).expect("No SKIA surface available.");
+    maze::draw_square(surface.canvas(), &maze, &style);
+
+    save(&mut surface, &output)?;
+
+    println!("===\n");
+
+    Ok(())
+}
+
+/// Reads the style of the walls of square mazes, `[--walls <lines|blocks|rounded|sketch>]`, the
+/// corridors being `[--corridor-ratio <ratio>]` times as wide as the walls, from 0.5 to 10.
+fn parse_style(walls: Option<&str>, corridor_ratio: Option<&str>) -> Result<maze::Style, String> {
+    let walls = match walls {
+        Some(walls) => walls.parse()?,
+        None => maze::Walls::default(),
+    };
+    let mut style = maze::Style::new(walls);
+    if let Some(ratio) = corridor_ratio {
+        style.corridor_ratio = ratio
+            .parse()
+            .ok()
+            .filter(|ratio| (0.5..=10.0).contains(ratio))
+            .ok_or_else(|| {
+                format!(
+                    "ERROR: the corridor ratio must be between 0.5 and 10, not `{}`",
+                    ratio
+                )
+            })?;
+    }
+
+    Ok(style)
+}
+
+/// Reads a size written as `<width>x<height>`, both above 0.
+fn parse_size<T: std::str::FromStr + Default + PartialOrd>(value: &str) -> Result<(T, T), String> {
+    value
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .filter(|(width, height)| *width > T::default() && *height > T::default())
+        .ok_or_else(|| format!("ERROR: `{}` is not a size like 1920x1080", value))
+}
+
+/// Reads command line options written as `--name value` pairs.
+fn parse_options(args: &[String]) -> Result<Vec<(&str, &str)>, String> {
+    args.chunks(2)
+        .map(|pair| match pair {
+            [name, value] if name.starts_with("--") => Ok((&name[2..], value.as_str())),
+            [name] if name.starts_with("--") => {
+                Err(format!("ERROR: missing a value for `{}`", name))
+            }
+            _ => Err(format!("ERROR: unexpected argument `{}`", pair[0])),
+        })
+        .collect()
+}
+
+fn export_tree_skeleton() -> Result<(), String> {
+    println!("===\nExporting tree skeleton");
+
+    create_dir_all("exports")
+        .map_err(|_e| "ERROR: Couldn't create the `exports` directory".to_string())?;
+
+    let skeleton = tree::Skeleton::from_painting();
+    write_file("exports/tree_skeleton.json", skeleton.to_json()?.as_bytes())?;
+    write_file("exports/tree_skeleton.csv", skeleton.to_csv().as_bytes())?;
+
+    println!("===\n");
+
+    Ok(())
+}
+
+fn write_file(file_name: &str, content: &[u8]) -> Result<(), String> {
+    let mut file = File::create(file_name)
+        .map_err(|_e| format!("ERROR: failed to create the file `{}`", file_name))?;
+    file.write_all(content)
+        .map_err(|_e| format!("ERROR: failed to write in the file `{}`", file_name))
+}
+
 fn draw(draw_fn: fn(&mut Canvas) -> (), output: &str, size: (i32, i32)) -> Result<(), String> {
     println!("===\nDrawing {}", output);
 
@@ -54,6 +680,16 @@ fn draw(draw_fn: fn(&mut Canvas) -> (), output: &str, size: (i32, i32)) -> Resul
 
     draw_fn(canvas);
 
+    save(&mut surface, output)?;
+
+    println!("===\n");
+
+    Ok(())
+}
+
+fn save(surface: &mut Surface, output: &str) -> Result<(), String> {
+    let (width, height) = (surface.width(), surface.height());
+
     // Save the result.
     create_dir_all("images")
         .map_err(|_e| "ERROR: Coudn\'t create the `rendering` directory".to_string())?;
@@ -79,8 +715,6 @@ fn draw(draw_fn: fn(&mut Canvas) -> (), output: &str, size: (i32, i32)) -> Resul
         }
     };
 
-    println!("===\n");
-
     Ok(())
 }
 
@@ -0,0 +1,269 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::{rngs::StdRng, SeedableRng};
+use skia_safe::{Canvas, Color, Paint, Rect};
+
+use super::solver::Step;
+use super::{
+    border, fit_size, inside_cells, Cell, CellType, Maze, MazeOptions, Overlay, Position, Shape,
+    Style,
+};
+use crate::utils::{Bounded, Palette};
+
+/// Color of the cells a solver went through.
+const VISITED_COLOR: Color = Palette::DARK_BEIGE;
+/// Color of the cells a solver turned back from.
+const BACKTRACKED_COLOR: Color = Palette::DARKER_BEIGE;
+/// Size of the mark of the last cell carved by a generator, in cells of the full grid.
+const CARVING_MARKER_RADIUS: f32 = 0.6;
+
+/// What the animation of a square maze shows.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Animation {
+    /// The walls being carved, step by step, by the generator.
+    Generation,
+    /// The cells visited by the solver searching for the exit, then the solution.
+    #[default]
+    Solution,
+    /// The generation, then the solution, each over half of the frames.
+    Both,
+}
+
+impl Animation {
+    pub const ALL: [Animation; 3] = [Animation::Generation, Animation::Solution, Animation::Both];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Animation::Generation => "generation",
+            Animation::Solution => "solution",
+            Animation::Both => "both",
+        }
+    }
+}
+
+impl Display for Animation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Animation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Animation::ALL
+            .iter()
+            .find(|animation| animation.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Animation::ALL.iter().map(|a| a.name()).collect();
+                format!(
+                    "ERROR: unknown animation `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// A square maze with what happened while it was built and solved, to draw it frame by frame.
+pub struct MazeAnimation {
+    animation: Animation,
+    /// The maze before the generator carved it.
+    blank: Maze,
+    /// Every change made to the cells of the blank maze, in order, until it was finished.
+    changes: Vec<(usize, Cell)>,
+    maze: Maze,
+    /// The search of the solver, from the entry to the exit of the solution.
+    steps: Vec<Step<Position>>,
+    style: Style,
+}
+
+impl MazeAnimation {
+    /// Animates the maze `Maze::fit` would draw on the canvas.
+    pub fn fit(
+        canvas: &Canvas,
+        options: &MazeOptions,
+        animation: Animation,
+    ) -> Result<Self, String> {
+        let (width, height) = fit_size(canvas, options);
+        MazeAnimation::new(width, height, options, animation)
+    }
+
+    fn new(
+        width: usize,
+        height: usize,
+        options: &MazeOptions,
+        animation: Animation,
+    ) -> Result<Self, String> {
+        if options.shape != Shape::Square || options.levels > 1 {
+            return Err("ERROR: only square mazes of a single level can be animated".to_string());
+        }
+
+        let inside = inside_cells(width, height, options)?;
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        let blank = Maze::prepare(width, height, inside, options)?;
+
+        let mut maze = blank.clone();
+        maze.data.record();
+        let mut maze = maze.build(options, &mut rng);
+        let changes = maze.data.take_changes();
+
+        let mut steps = Vec::new();
+        let exit = maze.solution.as_ref().and_then(|s| s.path.last().copied());
+        if let (Some(doors), Some(exit)) = (&maze.doors, exit) {
+            options
+                .effective_solver()
+                .trace(&maze, doors.entry, exit, &mut |step| steps.push(step));
+        }
+
+        Ok(MazeAnimation {
+            animation,
+            blank,
+            changes,
+            maze,
+            steps,
+            style: options.style,
+        })
+    }
+
+    pub fn maze(&self) -> &Maze {
+        &self.maze
+    }
+
+    /// Draws the frame `frame` out of `frames`, the first one showing the maze before the
+    /// generator or the solver starts, and the last one the finished maze.
+    pub fn draw_frame(&self, canvas: &mut Canvas, frame: usize, frames: usize) {
+        let generation_frames = match self.animation {
+            Animation::Generation => frames,
+            Animation::Solution => 0,
+            Animation::Both => frames / 2,
+        };
+
+        let (border_x, border_y) = border(self.maze.wrap);
+        let bounds = Rect::new(
+            border_x,
+            border_y,
+            canvas.width() - border_x,
+            canvas.height() - border_y,
+        );
+        canvas.clear(Palette::BEIGE);
+        match frame.checked_sub(generation_frames) {
+            None => self.draw_generation(canvas, bounds, progress(frame, generation_frames)),
+            Some(frame) => {
+                self.draw_search(canvas, bounds, progress(frame, frames - generation_frames))
+            }
+        }
+    }
+
+    /// The maze once a share of the changes of the generator are made.
+    fn draw_generation(&self, canvas: &mut Canvas, bounds: Rect, progress: f32) {
+        let count = (self.changes.len() as f32 * progress).round() as usize;
+        let mut maze = self.blank.clone();
+        for (index, cell) in self.changes[..count].iter() {
+            maze.data.set(*index, *cell);
+        }
+        // The cells not carved yet are drawn as closed rooms, rather than as solid walls.
+        for y in (1..maze.height * 2).step_by(2) {
+            for x in (1..maze.width * 2).step_by(2) {
+                match maze.get_any_cell(x, y) {
+                    Some(cell) if cell.cell_type == CellType::Wall && !cell.visited => {
+                        let cell_type = CellType::Floor;
+                        maze.set_any_cell(x, y, Cell { cell_type, ..cell });
+                    }
+                    _ => (),
+                }
+            }
+        }
+        maze.draw_in(canvas, bounds, Overlay::Markers, &self.style);
+
+        if let Some((index, _)) = self.changes[..count].last() {
+            if count < self.changes.len() {
+                let mut paint = Paint::default();
+                paint.set_anti_alias(true);
+                paint.set_color(Palette::RED);
+
+                let columns = maze.width * 2 + 1;
+                let (x, y) = ((index % columns) as f32, (index / columns) as f32);
+                canvas.save();
+                maze.transform(canvas, bounds);
+                canvas.draw_circle((x, y), CARVING_MARKER_RADIUS, &paint);
+                canvas.restore();
+            }
+        }
+    }
+
+    /// The cells visited by the solver once it went through a share of its steps, and the
+    /// solution after the last one.
+    fn draw_search(&self, canvas: &mut Canvas, bounds: Rect, progress: f32) {
+        let count = (self.steps.len() as f32 * progress).round() as usize;
+
+        // Not anti-aliased, so neighbouring cells don't leave a seam between them.
+        let mut paint = Paint::default();
+        canvas.save();
+        self.maze.transform(canvas, bounds);
+        for step in self.steps[..count].iter() {
+            let (Position(x, y), color) = match step {
+                Step::Visit(position) => (position, VISITED_COLOR),
+                Step::Backtrack(position) => (position, BACKTRACKED_COLOR),
+            };
+            let (x, y) = (*x as f32, *y as f32);
+            paint.set_color(color);
+            canvas.draw_rect(Rect::new(x - 0.5, y - 0.5, x + 0.5, y + 0.5), &paint);
+        }
+        canvas.restore();
+
+        let overlay = match count == self.steps.len() {
+            true => Overlay::Solution,
+            false => Overlay::Markers,
+        };
+        self.maze.draw_in(canvas, bounds, overlay, &self.style);
+    }
+}
+
+/// How far along an animation of `frames` frames the frame `frame` is, from 0.0 to 1.0.
+fn progress(frame: usize, frames: usize) -> f32 {
+    match frames {
+        0 | 1 => 1.0,
+        _ => frame as f32 / (frames - 1) as f32,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::animation::*;
+    use crate::maze::{Generator, Solver};
+
+    #[test]
+    fn replays_the_generation_and_the_search() {
+        for solver in Solver::ALL {
+            let options = MazeOptions {
+                generator: Generator::Prim,
+                solver,
+                braid: 0.5,
+                ..MazeOptions::default()
+            };
+            let animation = MazeAnimation::new(9, 7, &options, Animation::Both).unwrap();
+
+            // Replaying the changes carves the same maze as without an animation.
+            let mut replayed = animation.blank.clone();
+            for (index, cell) in animation.changes.iter() {
+                replayed.data.set(*index, *cell);
+            }
+            let maze = Maze::new(9, 7, &options).unwrap();
+            assert!(replayed
+                .data
+                .iter()
+                .zip(maze.data.iter())
+                .all(|(a, b)| a.cell_type == b.cell_type));
+
+            // The solver goes through every cell of the solution, never turning back from one.
+            let path = &maze.solution.as_ref().unwrap().path;
+            for position in path {
+                assert!(animation.steps.contains(&Step::Visit(*position)));
+                assert!(!animation.steps.contains(&Step::Backtrack(*position)));
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use skia_safe::{Canvas, Font, Paint, Rect, Typeface};
+
+use super::{search_seed, Maze, MazeOptions, Overlay, Shape, Style};
+use crate::utils::Palette;
+
+/// Resolution of the pages, in dots per inch.
+const DPI: f32 = 150.0;
+const MILLIMETERS_PER_INCH: f32 = 25.4;
+/// Blank space around the pages, where most printers can't print, in millimeters.
+const MARGIN: f32 = 15.0;
+/// Height of the title above the mazes and of the page number below them, in millimeters.
+const LINE_HEIGHT: f32 = 10.0;
+/// Size of the letters, in millimeters.
+const TEXT_SIZE: f32 = 4.0;
+/// Width of the cells of the puzzles, in millimeters, wide enough to draw a line through them.
+const CELL_SIZE: f32 = 6.0;
+/// Solutions on each row, and in each column, of an answer key page.
+const ANSWERS_PER_SIDE: usize = 2;
+const ANSWERS_PER_PAGE: usize = ANSWERS_PER_SIDE * ANSWERS_PER_SIDE;
+
+/// The paper a puzzle book is printed on.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Paper {
+    A4,
+    Letter,
+}
+
+impl Paper {
+    pub const ALL: [Paper; 2] = [Paper::A4, Paper::Letter];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Paper::A4 => "a4",
+            Paper::Letter => "letter",
+        }
+    }
+
+    /// Width and height, in millimeters.
+    fn size(&self) -> (f32, f32) {
+        match self {
+            Paper::A4 => (210.0, 297.0),
+            Paper::Letter => (215.9, 279.4),
+        }
+    }
+
+    /// Width and height of the pages, in pixels.
+    pub fn pixels(&self) -> (i32, i32) {
+        let (width, height) = self.size();
+        (
+            to_pixels(width).round() as i32,
+            to_pixels(height).round() as i32,
+        )
+    }
+
+    /// Where the mazes go: inside the margins, between the title and the page number.
+    fn content(&self) -> Rect {
+        let (width, height) = self.size();
+        Rect::new(
+            to_pixels(MARGIN),
+            to_pixels(MARGIN + LINE_HEIGHT),
+            to_pixels(width - MARGIN),
+            to_pixels(height - MARGIN - LINE_HEIGHT),
+        )
+    }
+}
+
+impl Display for Paper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Paper {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Paper::ALL
+            .iter()
+            .find(|paper| paper.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Paper::ALL.iter().map(|p| p.name()).collect();
+                format!(
+                    "ERROR: unknown paper `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+fn to_pixels(millimeters: f32) -> f32 {
+    millimeters / MILLIMETERS_PER_INCH * DPI
+}
+
+/// A puzzle book: a maze on each page, with markers at its entry and exit but without its
+/// solution, then answer keys with the solutions of several mazes on each page.
+pub struct Book {
+    paper: Paper,
+    mazes: Vec<Maze>,
+    style: Style,
+}
+
+impl Book {
+    /// Fills `count` pages with square mazes, their seeds following the one of the options. With
+    /// a difficulty, from 0 to 100, the seeds are searched for mazes of that difficulty, trying
+    /// up to a number of seeds for each maze.
+    pub fn new(
+        paper: Paper,
+        count: usize,
+        options: &MazeOptions,
+        difficulty: Option<(f32, u64)>,
+    ) -> Result<Self, String> {
+        if options.shape != Shape::Square {
+            return Err("ERROR: only square mazes can be printed in a book".to_string());
+        }
+        if options.levels > 1 {
+            return Err("ERROR: only mazes of a single level can be printed in a book".to_string());
+        }
+        if options.cells.is_some() {
+            return Err("ERROR: the mazes of a book are as big as fit its pages".to_string());
+        }
+
+        let content = paper.content();
+        let width = (content.width() / to_pixels(CELL_SIZE)) as usize;
+        let height = (content.height() / to_pixels(CELL_SIZE)) as usize;
+        let build = |options: &MazeOptions| Maze::new(width, height, options);
+
+        let mut mazes = Vec::with_capacity(count);
+        let mut seed = options.seed;
+        for page in 1..=count {
+            let mut options = MazeOptions {
+                seed,
+                ..options.clone()
+            };
+            if let Some((difficulty, tries)) = difficulty {
+                options.seed = search_seed(&options, difficulty, tries, |options| {
+                    Ok(build(options)?.statistics())
+                })?;
+            }
+
+            let maze = build(&options)?;
+            println!(
+                "Page {}: seed {}, difficulty {:.1}",
+                page,
+                options.seed,
+                maze.statistics().difficulty()
+            );
+            mazes.push(maze);
+            // The next search starts after the seed found, so pages don't repeat a maze.
+            seed = options.seed.wrapping_add(1);
+        }
+
+        Ok(Book {
+            paper,
+            mazes,
+            style: options.style,
+        })
+    }
+
+    pub fn paper(&self) -> Paper {
+        self.paper
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.mazes.len() + self.mazes.len().div_ceil(ANSWERS_PER_PAGE)
+    }
+
+    /// Draws a page, numbered from 1 to `page_count`: the puzzles first, then the answer keys.
+    pub fn draw_page(&self, canvas: &mut Canvas, page: usize) -> Result<(), String> {
+        if !(1..=self.page_count()).contains(&page) {
+            return Err(format!(
+                "ERROR: the book has no page {}, only pages 1 to {}",
+                page,
+                self.page_count()
+            ));
+        }
+
+        canvas.clear(Palette::WHITE);
+        let content = self.paper.content();
+
+        match self.mazes.get(page - 1) {
+            Some(maze) => maze.draw_in(canvas, fit(maze, content), Overlay::Markers, &self.style),
+            None => {
+                let title = content.top - to_pixels(LINE_HEIGHT) / 2.0;
+                draw_text(canvas, "Answers", content.center_x(), title);
+
+                let first = (page - 1 - self.mazes.len()) * ANSWERS_PER_PAGE;
+                let width = content.width() / ANSWERS_PER_SIDE as f32;
+                let height = content.height() / ANSWERS_PER_SIDE as f32;
+                let padding = to_pixels(LINE_HEIGHT) / 2.0;
+                for (index, maze) in self
+                    .mazes
+                    .iter()
+                    .enumerate()
+                    .skip(first)
+                    .take(ANSWERS_PER_PAGE)
+                {
+                    let slot = index - first;
+                    let left = content.left + (slot % ANSWERS_PER_SIDE) as f32 * width;
+                    let top = content.top + (slot / ANSWERS_PER_SIDE) as f32 * height;
+                    // Leaving room under the solution for the page of the puzzle.
+                    let area = Rect::new(
+                        left + padding,
+                        top + padding,
+                        left + width - padding,
+                        top + height - padding * 2.0,
+                    );
+                    maze.draw_in(canvas, fit(maze, area), Overlay::Solution, &self.style);
+
+                    let label = format!("Page {}", index + 1);
+                    draw_text(canvas, &label, area.center_x(), area.bottom + padding);
+                }
+            }
+        }
+
+        let (width, height) = self.paper.size();
+        let bottom = to_pixels(height - MARGIN - LINE_HEIGHT / 2.0);
+        draw_text(canvas, &page.to_string(), to_pixels(width / 2.0), bottom);
+
+        Ok(())
+    }
+}
+
+/// The largest bounds with the proportions of the maze centered in `area`, so cells are square.
+fn fit(maze: &Maze, area: Rect) -> Rect {
+    let (columns, rows) = maze.grid_size();
+    let scale = (area.width() / columns).min(area.height() / rows);
+    let (width, height) = (columns * scale, rows * scale);
+
+    Rect::from_xywh(
+        area.center_x() - width / 2.0,
+        area.center_y() - height / 2.0,
+        width,
+        height,
+    )
+}
+
+/// Writes a line of text centered horizontally on `x`, and vertically on `y`.
+fn draw_text(canvas: &mut Canvas, text: &str, x: f32, y: f32) {
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    paint.set_color(Palette::BLACK);
+
+    let font = Font::new(Typeface::default(), to_pixels(TEXT_SIZE));
+    let (_, bounds) = font.measure_str(text, Some(&paint));
+    canvas.draw_str(
+        text,
+        (x - bounds.center_x(), y - bounds.center_y()),
+        &font,
+        &paint,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::book::*;
+    use skia_safe::Surface;
+
+    #[test]
+    fn lays_out_puzzles_then_answers() {
+        let book = Book::new(Paper::Letter, 5, &MazeOptions::default(), None).unwrap();
+        assert_eq!(book.page_count(), 5 + 2);
+
+        // Cells of 6 millimeters inside the margins of the page.
+        let (width, height) = Paper::A4.pixels();
+        assert_eq!((width, height), (1240, 1754));
+        let maze = &Book::new(Paper::A4, 1, &MazeOptions::default(), None)
+            .unwrap()
+            .mazes[0];
+        assert_eq!((maze.width, maze.height), (30, 41));
+
+        // Each page has its own maze.
+        assert_ne!(book.mazes[0].to_ascii(), book.mazes[1].to_ascii());
+
+        // Pages are numbered from 1.
+        let mut surface = Surface::new_raster_n32_premul(Paper::Letter.pixels()).unwrap();
+        assert!(book.draw_page(surface.canvas(), 0).is_err());
+        assert!(book.draw_page(surface.canvas(), 7).is_ok());
+        assert!(book.draw_page(surface.canvas(), 8).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::{rngs::StdRng, Rng};
+
+use super::grid::Grid;
+use super::solver::Graph;
+use super::{Cell, CellType, Maze, Position, Solver};
+
+/// Where a square maze is entered or left: through a random opening of one of its sides, or at
+/// a random cell inside of it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Door {
+    North,
+    South,
+    East,
+    West,
+    Inside,
+}
+
+impl Door {
+    pub const ALL: [Door; 5] = [
+        Door::North,
+        Door::South,
+        Door::East,
+        Door::West,
+        Door::Inside,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Door::North => "north",
+            Door::South => "south",
+            Door::East => "east",
+            Door::West => "west",
+            Door::Inside => "inside",
+        }
+    }
+}
+
+impl Display for Door {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Door {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Door::ALL
+            .iter()
+            .find(|door| door.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Door::ALL.iter().map(|d| d.name()).collect();
+                format!(
+                    "ERROR: unknown door `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// How the entry and the exits of a square maze are placed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Placement {
+    /// An entry and at least one exit, solutions leading to the closest exit.
+    Doors { entry: Door, exits: Vec<Door> },
+    /// The entry and the exit inside the maze, at the two cells farthest apart, so the solution
+    /// is as long as it gets.
+    LongestPath,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Placement::Doors {
+            entry: Door::West,
+            exits: vec![Door::East],
+        }
+    }
+}
+
+/// The entry and the exits of a square maze, on the full grid: openings in the walls of the
+/// sides, or cells inside.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Doors {
+    pub(super) entry: Position,
+    pub(super) exits: Vec<Position>,
+}
+
+impl Maze {
+    /// Places the entry and the exits, opening the walls of the doors on the sides.
+    pub(super) fn open_doors(mut self, placement: &Placement, rng: &mut StdRng) -> Self {
+        let doors = match placement {
+            Placement::Doors { entry, exits } => {
+                let entry = match self.place_door(*entry, rng) {
+                    Some(entry) => entry,
+                    None => return self,
+                };
+                let mut doors = Doors {
+                    entry,
+                    exits: Vec::with_capacity(exits.len()),
+                };
+                for exit in exits {
+                    if let Some(exit) = self.place_door(*exit, rng) {
+                        if exit != doors.entry && !doors.exits.contains(&exit) {
+                            doors.exits.push(exit);
+                        }
+                    }
+                }
+                // In reading order, as in ASCII art.
+                doors.exits.sort_by_key(|Position(x, y)| (*y, *x));
+                doors
+            }
+            Placement::LongestPath => match farthest_cells(&self) {
+                Some((Position(x, y), Position(exit_x, exit_y))) => Doors {
+                    entry: Position(x * 2 + 1, y * 2 + 1),
+                    exits: vec![Position(exit_x * 2 + 1, exit_y * 2 + 1)],
+                },
+                None => return self,
+            },
+        };
+
+        for Position(x, y) in [doors.entry].iter().chain(doors.exits.iter()) {
+            match self.get_any_cell(*x, *y) {
+                Some(cell) if cell.cell_type == CellType::Wall => self.set_any_cell(
+                    *x,
+                    *y,
+                    Cell {
+                        cell_type: CellType::Floor,
+                        ..cell
+                    },
+                ),
+                _ => (),
+            }
+        }
+        self.doors = Some(doors);
+
+        self
+    }
+
+    /// A random door of a kind, on the full grid. Doors on a side open the wall of the first,
+    /// or last, cell inside the mask of a random row, or column.
+    fn place_door(&self, door: Door, rng: &mut StdRng) -> Option<Position> {
+        let (width, height) = (self.width, self.height);
+        let mut ends = |lines: usize, cells: usize, at: &dyn Fn(usize, usize) -> Position| {
+            let ends: Vec<(usize, usize, usize)> = (0..lines)
+                .filter_map(|line| {
+                    let mut inside = (0..cells).filter(|cell| self.is_inside(at(line, *cell)));
+                    let first = inside.next()?;
+                    Some((line, first, inside.next_back().unwrap_or(first)))
+                })
+                .collect();
+            match ends.is_empty() {
+                true => None,
+                false => Some(ends[rng.gen_range(0..ends.len())]),
+            }
+        };
+
+        match door {
+            Door::West => {
+                let (y, first, _) = ends(height, width, &|y, x| Position(x, y))?;
+                Some(Position(first * 2, y * 2 + 1))
+            }
+            Door::East => {
+                let (y, _, last) = ends(height, width, &|y, x| Position(x, y))?;
+                Some(Position(last * 2 + 2, y * 2 + 1))
+            }
+            Door::North => {
+                let (x, first, _) = ends(width, height, &|x, y| Position(x, y))?;
+                Some(Position(x * 2 + 1, first * 2))
+            }
+            Door::South => {
+                let (x, _, last) = ends(width, height, &|x, y| Position(x, y))?;
+                Some(Position(x * 2 + 1, last * 2 + 2))
+            }
+            Door::Inside => {
+                (0..self.index_count()).find_map(|index| self.cell_at(index))?;
+                // Not on a crossing, where it could be on the bridge as well as under it.
+                let Position(x, y) = loop {
+                    let cell = self.random_cell(rng);
+                    if self.crossing(cell).is_none() {
+                        break cell;
+                    }
+                };
+                Some(Position(x * 2 + 1, y * 2 + 1))
+            }
+        }
+    }
+
+    /// Which way a door on the full grid leads out of the maze, none for the doors inside.
+    pub(super) fn outward(&self, Position(x, y): Position) -> Option<(f32, f32)> {
+        let is_out = |x: Option<usize>, y: Option<usize>| match x
+            .zip(y)
+            .and_then(|(x, y)| self.get_any_cell(x, y))
+        {
+            Some(cell) => cell.cell_type == CellType::Outside,
+            None => true,
+        };
+
+        let sides = match (x % 2, y % 2) {
+            (0, 1) => [
+                ((-1.0, 0.0), x.checked_sub(1), Some(y)),
+                ((1.0, 0.0), Some(x + 1), Some(y)),
+            ],
+            (1, 0) => [
+                ((0.0, -1.0), Some(x), y.checked_sub(1)),
+                ((0.0, 1.0), Some(x), Some(y + 1)),
+            ],
+            _ => return None,
+        };
+        sides
+            .into_iter()
+            .find(|(_, x, y)| is_out(*x, *y))
+            .map(|(direction, _, _)| direction)
+    }
+
+    /// Solves the maze from its entry to each exit, keeping the shortest solution, going under a
+    /// crossing costing its two steps.
+    pub(super) fn solve_doors(&self, solver: Solver) -> Option<super::Solution> {
+        let doors = self.doors.as_ref()?;
+        let cost = |path: &[Position]| -> usize {
+            path.windows(2)
+                .map(|step| self.cost(step[0], step[1]))
+                .sum()
+        };
+        doors
+            .exits
+            .iter()
+            .filter_map(|exit| solver.solve(self, doors.entry, *exit))
+            .min_by_key(|solution| cost(&solution.path))
+    }
+}
+
+/// The two cells farthest apart along the passages: the farthest cell from any cell is one end
+/// of the longest path of a perfect maze, the other end being the farthest cell from it.
+pub(super) fn farthest_cells<G: Grid>(grid: &G) -> Option<(G::Cell, G::Cell)> {
+    let farthest = |from: G::Cell| {
+        let mut seen = vec![false; grid.index_count()];
+        let mut queue = VecDeque::from([from]);
+        let mut last = from;
+        seen[grid.cell_index(from)] = true;
+        while let Some(cell) = queue.pop_front() {
+            last = cell;
+            for next in grid.passages(cell) {
+                if !seen[grid.cell_index(next)] {
+                    seen[grid.cell_index(next)] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        last
+    };
+
+    let start = farthest((0..grid.index_count()).find_map(|index| grid.cell_at(index))?);
+    Some((start, farthest(start)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::doors::*;
+    use crate::maze::{Generator, MazeOptions};
+
+    #[test]
+    fn places_doors_anywhere() {
+        let options = MazeOptions {
+            placement: Placement::Doors {
+                entry: Door::North,
+                exits: vec![Door::South, Door::Inside],
+            },
+            solver: Solver::BreadthFirst,
+            ..MazeOptions::default()
+        };
+        let maze = Maze::new(9, 7, &options).unwrap();
+
+        let doors = maze.doors.as_ref().unwrap();
+        assert_eq!(doors.entry.1, 0);
+        assert_eq!(maze.outward(doors.entry), Some((0.0, -1.0)));
+        assert_eq!(doors.exits.len(), 2);
+        let (inside, south): (Vec<Position>, Vec<Position>) = doors
+            .exits
+            .iter()
+            .partition(|exit| maze.outward(**exit).is_none());
+        assert_eq!(inside.len(), 1);
+        assert_eq!(south[0].1, 7 * 2);
+
+        // The solution leads to the closest exit.
+        let solution = maze.solution().unwrap();
+        assert_eq!(solution.path.first(), Some(&doors.entry));
+        assert!(doors.exits.contains(solution.path.last().unwrap()));
+    }
+
+    #[test]
+    fn keeps_inner_doors_off_the_crossings() {
+        let mut crossings = 0;
+        for seed in 0..50 {
+            let options = MazeOptions {
+                placement: Placement::Doors {
+                    entry: Door::West,
+                    exits: vec![Door::Inside],
+                },
+                weave: true,
+                solver: Solver::BreadthFirst,
+                seed,
+                ..MazeOptions::default()
+            };
+            let maze = Maze::new(8, 8, &options).unwrap();
+            crossings += maze
+                .cells()
+                .into_iter()
+                .filter(|cell| maze.crossing(*cell).is_some())
+                .count();
+
+            let exit = maze.doors.as_ref().unwrap().exits[0];
+            assert_eq!(maze.crossing_at(exit.0, exit.1), None, "seed {}", seed);
+            assert_eq!(maze.solution().unwrap().path.last(), Some(&exit));
+        }
+        assert!(crossings > 0);
+    }
+
+    #[test]
+    fn finds_the_longest_path() {
+        for generator in [
+            Generator::DepthFirst,
+            Generator::HuntAndKill,
+            Generator::Wilson,
+            Generator::Prim,
+        ] {
+            let options = MazeOptions {
+                generator,
+                solver: Solver::BreadthFirst,
+                placement: Placement::LongestPath,
+                ..MazeOptions::default()
+            };
+            let maze = Maze::new(12, 8, &options).unwrap();
+            let longest = maze.solution().unwrap().path.len();
+
+            // No two cells are farther apart than the entry and the exit.
+            let cells = maze.cells();
+            for Position(x, y) in cells.iter().copied() {
+                for Position(to_x, to_y) in cells.iter().copied().step_by(7) {
+                    let start = Position(x * 2 + 1, y * 2 + 1);
+                    let goal = Position(to_x * 2 + 1, to_y * 2 + 1);
+                    let solution = Solver::BreadthFirst.solve(&maze, start, goal).unwrap();
+                    assert!(solution.path.len() <= longest);
+                }
+            }
+        }
+    }
+}
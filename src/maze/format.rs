@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+
+use super::packed::PackedCells;
+use super::{Axis, CellType, Doors, Maze, Position, Solution, Wrap};
+
+/// What a square maze is saved as in JSON. Positions are the ones of the full grid, walls
+/// included, as `[x, y]`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Description {
+    width: usize,
+    height: usize,
+    /// Rows of the full grid: `#` for a wall, a space for a floor, `.` outside of the mask, and
+    /// `|` or `-` for a crossing, after the corridor going over it.
+    walls: Vec<String>,
+    entry: Option<Position>,
+    #[serde(default)]
+    exits: Vec<Position>,
+    solution: Option<Solution>,
+    /// Which sides lead to the opposite ones, none when it is left out.
+    #[serde(default)]
+    wrap: Wrap,
+}
+
+/// Marks of the entry and of the exits in ASCII art.
+const ENTRY: char = 'S';
+const EXIT: char = 'E';
+
+impl CellType {
+    fn as_char(&self) -> char {
+        match self {
+            CellType::Wall => '#',
+            CellType::Floor => ' ',
+            CellType::Outside => '.',
+            CellType::Crossing(Axis::Vertical) => '|',
+            CellType::Crossing(Axis::Horizontal) => '-',
+        }
+    }
+
+    fn from_char(c: char) -> Option<CellType> {
+        match c {
+            '#' => Some(CellType::Wall),
+            ' ' => Some(CellType::Floor),
+            '.' => Some(CellType::Outside),
+            '|' => Some(CellType::Crossing(Axis::Vertical)),
+            '-' => Some(CellType::Crossing(Axis::Horizontal)),
+            _ => None,
+        }
+    }
+}
+
+impl Maze {
+    /// Rows of the full grid, a character per cell.
+    fn rows(&self) -> Vec<String> {
+        (0..self.height * 2 + 1)
+            .map(|y| {
+                (0..self.width * 2 + 1)
+                    .filter_map(|x| self.get_any_cell(x, y))
+                    .map(|cell| cell.cell_type.as_char())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The full grid as ASCII art, characters being spaced so the maze keeps its proportions,
+    /// with `S` at the entry and `E` at the exits.
+    pub fn to_ascii(&self) -> String {
+        let mut rows: Vec<Vec<char>> = self
+            .rows()
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        if let Some(doors) = &self.doors {
+            let marks = [(doors.entry, ENTRY)]
+                .into_iter()
+                .chain(doors.exits.iter().map(|exit| (*exit, EXIT)));
+            for (Position(x, y), mark) in marks {
+                rows[y][x] = mark;
+            }
+        }
+
+        rows.iter()
+            .map(|row| {
+                let cells: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+                cells.join(" ") + "\n"
+            })
+            .collect()
+    }
+
+    /// Reads back the ASCII art of `to_ascii`. Without marks, the entry is the first opening of
+    /// the sides facing west, and the exits are the other ones.
+    pub fn from_ascii(ascii: &str) -> Result<Self, String> {
+        let mut rows: Vec<String> = ascii
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.chars().step_by(2).collect())
+            .collect();
+        // Editors might have trimmed the spaces of an exit on the east side.
+        let columns = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        for row in rows.iter_mut() {
+            let missing = columns - row.chars().count();
+            row.push_str(&" ".repeat(missing));
+        }
+
+        let mut entry = None;
+        let mut exits = Vec::new();
+        for (y, row) in rows.iter_mut().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    ENTRY => entry = Some(Position(x, y)),
+                    EXIT => exits.push(Position(x, y)),
+                    _ => (),
+                }
+            }
+            *row = row.replace([ENTRY, EXIT], " ");
+        }
+
+        let maze = Maze::from_rows(&rows)?;
+        let doors = match entry {
+            Some(entry) => Some(Doors { entry, exits }),
+            None => maze.find_doors(),
+        };
+        Ok(Maze { doors, ..maze })
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        let description = Description {
+            width: self.width,
+            height: self.height,
+            walls: self.rows(),
+            entry: self.doors.as_ref().map(|doors| doors.entry),
+            exits: self
+                .doors
+                .as_ref()
+                .map(|doors| doors.exits.clone())
+                .unwrap_or_default(),
+            solution: self.solution.clone(),
+            wrap: self.wrap,
+        };
+
+        serde_json::to_string_pretty(&description)
+            .map_err(|e| format!("ERROR: failed to export the maze to JSON: {}", e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let description: Description = serde_json::from_str(json)
+            .map_err(|e| format!("ERROR: failed to read the maze JSON: {}", e))?;
+
+        let maze = Maze::from_rows(&description.walls)?;
+        if (maze.width, maze.height) != (description.width, description.height) {
+            return Err(format!(
+                "ERROR: the walls of the maze don't match its dimension {}x{}",
+                description.width, description.height
+            ));
+        }
+
+        let doors = match description.entry {
+            Some(entry) if !description.exits.is_empty() => Some(Doors {
+                entry,
+                exits: description.exits,
+            }),
+            _ => maze.find_doors(),
+        };
+        let positions = doors
+            .iter()
+            .flat_map(|doors| [doors.entry].into_iter().chain(doors.exits.clone()))
+            .chain(description.solution.iter().flat_map(|s| s.path.clone()));
+        for Position(x, y) in positions {
+            if maze.get_any_cell(x, y).is_none() {
+                return Err(format!(
+                    "ERROR: the position [{}, {}] is outside of the maze",
+                    x, y
+                ));
+            }
+        }
+
+        Ok(Maze {
+            doors,
+            solution: description.solution,
+            wrap: description.wrap,
+            ..maze
+        })
+    }
+
+    fn from_rows(rows: &[String]) -> Result<Self, String> {
+        let columns = rows.first().map(|row| row.chars().count()).unwrap_or(0);
+        if rows.len() < 3
+            || columns < 3
+            || rows.len().is_multiple_of(2)
+            || columns.is_multiple_of(2)
+        {
+            return Err(format!(
+                "ERROR: a maze needs an odd number of rows and columns of at least 3, not {}x{}",
+                columns,
+                rows.len()
+            ));
+        }
+
+        let mut data = PackedCells::new(columns * rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != columns {
+                return Err(format!(
+                    "ERROR: row {} of the maze has {} cells instead of {}",
+                    y,
+                    row.chars().count(),
+                    columns
+                ));
+            }
+            for (x, c) in row.chars().enumerate() {
+                let cell_type = CellType::from_char(c)
+                    .ok_or_else(|| format!("ERROR: unexpected character `{}` in the maze", c))?;
+                data.set_type(y * columns + x, cell_type);
+            }
+        }
+
+        Ok(Maze {
+            width: columns / 2,
+            height: rows.len() / 2,
+            data,
+            doors: None,
+            solution: None,
+            weave: false,
+            wrap: Wrap::None,
+        })
+    }
+
+    /// The openings of the sides of the full grid, row by row: the first one facing west is
+    /// the entry, or the first one if none does, the other ones are the exits.
+    fn find_doors(&self) -> Option<Doors> {
+        let mut openings: Vec<Position> = (0..self.height * 2 + 1)
+            .flat_map(|y| (0..self.width * 2 + 1).map(move |x| Position(x, y)))
+            .filter(|Position(x, y)| (x + y) % 2 == 1)
+            .filter(|Position(x, y)| {
+                self.get_any_cell(*x, *y)
+                    .map(|cell| cell.cell_type == CellType::Floor)
+                    .unwrap_or(false)
+            })
+            .filter(|position| self.outward(*position).is_some())
+            .collect();
+
+        let entry = openings
+            .iter()
+            .position(|position| self.outward(*position) == Some((-1.0, 0.0)))
+            .unwrap_or(0);
+        if openings.len() < 2 {
+            return None;
+        }
+        let entry = openings.remove(entry);
+
+        Some(Doors {
+            entry,
+            exits: openings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::format::*;
+    use crate::maze::{Generator, MazeOptions, Solver};
+
+    #[test]
+    fn round_trips_through_ascii_and_json() {
+        let options = MazeOptions {
+            generator: Generator::Wilson,
+            solver: Solver::BreadthFirst,
+            braid: 0.5,
+            ..MazeOptions::default()
+        };
+        let maze = Maze::new(12, 7, &options).unwrap();
+
+        let from_ascii = Maze::from_ascii(&maze.to_ascii()).unwrap();
+        assert_eq!(from_ascii.rows(), maze.rows());
+        assert_eq!(from_ascii.doors, maze.doors);
+        assert!(from_ascii.solution.is_none());
+
+        let from_json = Maze::from_json(&maze.to_json().unwrap()).unwrap();
+        assert_eq!(from_json.rows(), maze.rows());
+        assert_eq!(from_json.doors, maze.doors);
+        assert_eq!(
+            from_json.solution.map(|solution| solution.path),
+            maze.solution.map(|solution| solution.path)
+        );
+    }
+
+    #[test]
+    fn rejects_broken_mazes() {
+        assert!(Maze::from_ascii("# # #\n#   #\n").is_err());
+        assert!(Maze::from_ascii("# # #\n# # # #\n# # #\n").is_err());
+        assert!(Maze::from_ascii("# # #\n# x #\n# # #\n").is_err());
+        assert!(Maze::from_json("{}").is_err());
+    }
+}
@@ -0,0 +1,587 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+use super::grid::Grid;
+use super::{Maze, Position};
+
+/// The algorithms available to carve a maze, most of them described on wikipedia
+/// (https://en.wikipedia.org/wiki/Maze_generation_algorithm) or in Jamis Buck's blog
+/// (https://weblog.jamisbuck.org/2011/2/7/maze-generation-algorithm-recap).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Generator {
+    /// Randomized depth-first search: long corridors, few branches.
+    DepthFirst,
+    /// Randomized Kruskal: joins random cells that are not yet connected.
+    Kruskal,
+    /// Randomized Prim: grows from a frontier, lots of short dead ends.
+    Prim,
+    /// Loop-erased random walks, giving an uniform spanning tree.
+    Wilson,
+    /// Plain random walk, also uniform, but slow to finish.
+    AldousBroder,
+    /// Row by row, keeping track of connected sets.
+    Eller,
+    /// Row by row, runs of east passages closed by a passage to the north.
+    Sidewinder,
+    /// Each cell opens either to the north or to the east.
+    BinaryTree,
+    /// Random walk, hunting for a new start next to the maze when stuck.
+    HuntAndKill,
+    /// Starts from an empty room, and keeps splitting it with walls.
+    RecursiveDivision,
+}
+
+impl Generator {
+    pub const ALL: [Generator; 10] = [
+        Generator::DepthFirst,
+        Generator::Kruskal,
+        Generator::Prim,
+        Generator::Wilson,
+        Generator::AldousBroder,
+        Generator::Eller,
+        Generator::Sidewinder,
+        Generator::BinaryTree,
+        Generator::HuntAndKill,
+        Generator::RecursiveDivision,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Generator::DepthFirst => "depth-first",
+            Generator::Kruskal => "kruskal",
+            Generator::Prim => "prim",
+            Generator::Wilson => "wilson",
+            Generator::AldousBroder => "aldous-broder",
+            Generator::Eller => "eller",
+            Generator::Sidewinder => "sidewinder",
+            Generator::BinaryTree => "binary-tree",
+            Generator::HuntAndKill => "hunt-and-kill",
+            Generator::RecursiveDivision => "recursive-division",
+        }
+    }
+
+    /// Whether the generator works row by row, only making sense on a rectangular grid.
+    pub fn is_rectangular_only(&self) -> bool {
+        matches!(
+            self,
+            Generator::Eller
+                | Generator::Sidewinder
+                | Generator::BinaryTree
+                | Generator::RecursiveDivision
+        )
+    }
+
+    /// Whether the generator can carve passages under the corridors of a weave maze, linking
+    /// the cells as soon as it picks them, while the corridors are still straight.
+    pub fn can_weave(&self) -> bool {
+        matches!(
+            self,
+            Generator::DepthFirst
+                | Generator::Prim
+                | Generator::AldousBroder
+                | Generator::HuntAndKill
+        )
+    }
+
+    /// Carves the rectangular maze, expecting it to be only walls.
+    pub(super) fn generate(&self, maze: &mut Maze, rng: &mut StdRng) {
+        match self {
+            Generator::Eller => eller(maze, rng),
+            Generator::Sidewinder => sidewinder(maze, rng),
+            Generator::BinaryTree => binary_tree(maze, rng),
+            Generator::RecursiveDivision => recursive_division(maze, rng),
+            _ => self.generate_on_grid(maze, rng),
+        }
+    }
+
+    /// Carves a maze of any shape, expecting it to be only walls.
+    pub(super) fn generate_on_grid<G: Grid>(&self, grid: &mut G, rng: &mut StdRng) {
+        match self {
+            Generator::DepthFirst => depth_first(grid, rng),
+            Generator::Kruskal => kruskal(grid, rng),
+            Generator::Prim => prim(grid, rng),
+            Generator::Wilson => wilson(grid, rng),
+            Generator::AldousBroder => aldous_broder(grid, rng),
+            Generator::HuntAndKill => hunt_and_kill(grid, rng),
+            Generator::Eller
+            | Generator::Sidewinder
+            | Generator::BinaryTree
+            | Generator::RecursiveDivision => {
+                unreachable!("{} only works on rectangular grids", self)
+            }
+        }
+    }
+}
+
+impl Display for Generator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Generator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Generator::ALL
+            .iter()
+            .find(|generator| generator.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Generator::ALL.iter().map(|g| g.name()).collect();
+                format!(
+                    "ERROR: unknown generator `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Uses the Randomized depth-first search found on wikipedia (https://en.wikipedia.org/wiki/Maze_generation_algorithm)
+/// to fill in the maze.
+fn depth_first<G: Grid>(grid: &mut G, rng: &mut StdRng) {
+    // The stack can hold most of the cells of the maze, so it keeps their indexes, which take a
+    // quarter of the memory of the positions of square mazes.
+    let mut cells: Vec<u32> = Vec::new();
+    // Initialize first cell, visited so the search never comes back to it through another wall.
+    let first_cell = grid.random_cell(rng);
+    grid.visit(first_cell);
+    cells.push(stack_index(grid, first_cell));
+
+    while let Some(current_cell) = cells.pop().and_then(|index| grid.cell_at(index as usize)) {
+        if let Some(neighboor_cell) = grid.random_unvisited_neighbour(current_cell, rng) {
+            cells.push(stack_index(grid, current_cell));
+            grid.link(current_cell, neighboor_cell);
+            // We could also do a pass initially to visit every cell, but it's not needed as this
+            // algo gives us the certainty that every cell will be visited anyway.
+            grid.visit(neighboor_cell);
+            cells.push(stack_index(grid, neighboor_cell));
+        }
+    }
+}
+
+/// The index of a cell on the stack of the depth-first search, mazes having at most as many
+/// cells as fit in a `u32`.
+fn stack_index<G: Grid>(grid: &G, cell: G::Cell) -> u32 {
+    u32::try_from(grid.cell_index(cell)).expect("Mazes have at most u32::MAX cells.")
+}
+
+fn kruskal<G: Grid>(grid: &mut G, rng: &mut StdRng) {
+    let cells = grid.cells();
+    let mut sets = DisjointSets::new(grid.index_count());
+
+    let mut edges: Vec<(G::Cell, G::Cell)> = Vec::new();
+    for cell in cells.iter().copied() {
+        // Reversed so a square cell lists its east edge before its south one: the shuffle depends
+        // on that order, which keeps the square mazes of a seed the same.
+        for neighbour in grid.neighbours(cell).into_iter().rev() {
+            if grid.cell_index(cell) < grid.cell_index(neighbour) {
+                edges.push((cell, neighbour));
+            }
+        }
+    }
+    edges.shuffle(rng);
+
+    for cell in cells {
+        grid.visit(cell);
+    }
+    for (a, b) in edges {
+        if sets.union(grid.cell_index(a), grid.cell_index(b)) {
+            grid.link(a, b);
+        }
+    }
+}
+
+fn prim<G: Grid>(grid: &mut G, rng: &mut StdRng) {
+    let start = grid.random_cell(rng);
+    grid.visit(start);
+
+    let mut frontier: Vec<G::Cell> = grid.neighbours(start);
+    while !frontier.is_empty() {
+        let cell = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+        if grid.is_visited(cell) {
+            continue;
+        }
+
+        let visited: Vec<G::Cell> = grid
+            .neighbours(cell)
+            .into_iter()
+            .filter(|neighbour| grid.is_visited(*neighbour))
+            .collect();
+        let into = visited[rng.gen_range(0..visited.len())];
+        grid.carve(cell, into);
+
+        frontier.extend(
+            grid.neighbours(cell)
+                .into_iter()
+                .filter(|neighbour| !grid.is_visited(*neighbour)),
+        );
+    }
+}
+
+fn wilson<G: Grid>(grid: &mut G, rng: &mut StdRng) {
+    let mut unvisited: Vec<G::Cell> = grid.cells();
+    unvisited.shuffle(rng);
+
+    if let Some(first) = unvisited.pop() {
+        grid.visit(first);
+    }
+
+    // For each cell of the walk, the next cell, so loops get erased when the walk crosses itself.
+    let mut next: Vec<Option<G::Cell>> = vec![None; grid.index_count()];
+    while let Some(start) = unvisited.pop() {
+        if grid.is_visited(start) {
+            continue;
+        }
+
+        let mut current = start;
+        while !grid.is_visited(current) {
+            let neighbours = grid.neighbours(current);
+            let neighbour = neighbours[rng.gen_range(0..neighbours.len())];
+            next[grid.cell_index(current)] = Some(neighbour);
+            current = neighbour;
+        }
+
+        let mut current = start;
+        loop {
+            let neighbour = next[grid.cell_index(current)].unwrap();
+            let reached_maze = grid.is_visited(neighbour);
+            grid.carve(current, neighbour);
+            if reached_maze {
+                break;
+            }
+            current = neighbour;
+        }
+    }
+}
+
+fn aldous_broder<G: Grid>(grid: &mut G, rng: &mut StdRng) {
+    let mut remaining = grid.cells().len() - 1;
+    let mut current = grid.random_cell(rng);
+    grid.visit(current);
+
+    while remaining > 0 {
+        let neighbours = grid.neighbours(current);
+        let neighbour = neighbours[rng.gen_range(0..neighbours.len())];
+        if !grid.is_visited(neighbour) {
+            grid.carve(neighbour, current);
+            remaining -= 1;
+        }
+        current = neighbour;
+    }
+}
+
+fn eller(maze: &mut Maze, rng: &mut StdRng) {
+    let (width, height) = (maze.width, maze.height);
+    maze.open_all_floor_cells();
+
+    // Set of each cell of the current row, sets being numbered across the whole maze.
+    let mut row: Vec<usize> = (0..width).collect();
+    let mut next_set = width;
+
+    for y in 0..height {
+        let last_row = y + 1 == height;
+
+        // Randomly join adjacent cells of different sets, or all of them on the last row.
+        for x in 0..width.saturating_sub(1) {
+            if row[x] != row[x + 1] && (last_row || rng.gen_bool(0.5)) {
+                maze.collapse_wall_between(Position(x, y), Position(x + 1, y));
+                let (from, to) = (row[x + 1], row[x]);
+                row.iter_mut()
+                    .filter(|set| **set == from)
+                    .for_each(|set| *set = to);
+            }
+        }
+
+        if last_row {
+            break;
+        }
+
+        // Each set goes down at least once, the other cells start new sets.
+        let mut next_row: Vec<Option<usize>> = vec![None; width];
+        let mut sets: Vec<usize> = row.clone();
+        sets.sort_unstable();
+        sets.dedup();
+        for set in sets {
+            let mut members: Vec<usize> = (0..width).filter(|x| row[*x] == set).collect();
+            members.shuffle(rng);
+            let count = rng.gen_range(1..=members.len());
+            for x in members.into_iter().take(count) {
+                maze.collapse_wall_between(Position(x, y), Position(x, y + 1));
+                next_row[x] = Some(set);
+            }
+        }
+
+        row = next_row
+            .into_iter()
+            .map(|set| {
+                set.unwrap_or_else(|| {
+                    let set = next_set;
+                    next_set += 1;
+                    set
+                })
+            })
+            .collect();
+    }
+}
+
+fn sidewinder(maze: &mut Maze, rng: &mut StdRng) {
+    let (width, height) = (maze.width, maze.height);
+    maze.open_all_floor_cells();
+
+    for y in 0..height {
+        let mut run_start = 0;
+        for x in 0..width {
+            let at_east_boundary = x + 1 == width;
+            let close_run = at_east_boundary || (y > 0 && rng.gen_bool(0.5));
+
+            if close_run {
+                if y > 0 {
+                    let member = rng.gen_range(run_start..=x);
+                    maze.collapse_wall_between(Position(member, y), Position(member, y - 1));
+                }
+                run_start = x + 1;
+            } else {
+                maze.collapse_wall_between(Position(x, y), Position(x + 1, y));
+            }
+        }
+    }
+}
+
+fn binary_tree(maze: &mut Maze, rng: &mut StdRng) {
+    let (width, height) = (maze.width, maze.height);
+    maze.open_all_floor_cells();
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut neighbours: Vec<Position> = Vec::with_capacity(2);
+            if y > 0 {
+                neighbours.push(Position(x, y - 1));
+            }
+            if x + 1 < width {
+                neighbours.push(Position(x + 1, y));
+            }
+            if let Some(neighbour) = neighbours.choose(rng) {
+                maze.collapse_wall_between(Position(x, y), *neighbour);
+            }
+        }
+    }
+}
+
+fn hunt_and_kill<G: Grid>(grid: &mut G, rng: &mut StdRng) {
+    let start = grid.random_cell(rng);
+    grid.visit(start);
+
+    let mut current = Some(start);
+
+    while let Some(cell) = current {
+        current = match grid.random_unvisited_neighbour(cell, rng) {
+            Some(neighbour) => {
+                grid.carve(neighbour, cell);
+                Some(neighbour)
+            }
+            None => hunt(grid, rng),
+        };
+    }
+}
+
+/// Scans the grid cell by cell for an unvisited cell next to the maze, and connects it.
+fn hunt<G: Grid>(grid: &mut G, rng: &mut StdRng) -> Option<G::Cell> {
+    for cell in grid.cells() {
+        if grid.is_visited(cell) {
+            continue;
+        }
+
+        let visited: Vec<G::Cell> = grid
+            .neighbours(cell)
+            .into_iter()
+            .filter(|neighbour| grid.is_visited(*neighbour))
+            .collect();
+        if let Some(neighbour) = visited.choose(rng) {
+            grid.carve(cell, *neighbour);
+            return Some(cell);
+        }
+    }
+
+    None
+}
+
+fn recursive_division(maze: &mut Maze, rng: &mut StdRng) {
+    let (width, height) = (maze.width, maze.height);
+    maze.open_all_floor_cells();
+    for y in 0..height {
+        for x in 0..width {
+            if x + 1 < width {
+                maze.collapse_wall_between(Position(x, y), Position(x + 1, y));
+            }
+            if y + 1 < height {
+                maze.collapse_wall_between(Position(x, y), Position(x, y + 1));
+            }
+        }
+    }
+
+    // Rooms still to divide, as (x, y, width, height), in cells.
+    let mut rooms: Vec<(usize, usize, usize, usize)> = vec![(0, 0, width, height)];
+    while let Some((x, y, room_width, room_height)) = rooms.pop() {
+        if room_width < 2 || room_height < 2 {
+            continue;
+        }
+
+        let horizontal = match room_width.cmp(&room_height) {
+            std::cmp::Ordering::Less => true,
+            std::cmp::Ordering::Greater => false,
+            std::cmp::Ordering::Equal => rng.gen_bool(0.5),
+        };
+
+        if horizontal {
+            // The wall goes below the row `wall`, with a single passage.
+            let wall = y + rng.gen_range(0..room_height - 1);
+            let passage = x + rng.gen_range(0..room_width);
+            for column in (x..x + room_width).filter(|column| *column != passage) {
+                maze.raise_wall_between(Position(column, wall), Position(column, wall + 1));
+            }
+            rooms.push((x, y, room_width, wall - y + 1));
+            rooms.push((x, wall + 1, room_width, y + room_height - wall - 1));
+        } else {
+            // The wall goes right of the column `wall`, with a single passage.
+            let wall = x + rng.gen_range(0..room_width - 1);
+            let passage = y + rng.gen_range(0..room_height);
+            for row in (y..y + room_height).filter(|row| *row != passage) {
+                maze.raise_wall_between(Position(wall, row), Position(wall + 1, row));
+            }
+            rooms.push((x, y, wall - x + 1, room_height));
+            rooms.push((wall + 1, y, x + room_width - wall - 1, room_height));
+        }
+    }
+}
+
+/// Removes a `ratio` of the dead ends, from 0.0 (none, the maze stays perfect) to 1.0 (all of
+/// them), by knocking down one of their walls. It creates loops, preferably by joining two dead
+/// ends together, which removes both at once.
+pub(super) fn braid<G: Grid>(grid: &mut G, ratio: f32, rng: &mut StdRng) {
+    if ratio <= 0.0 {
+        return;
+    }
+
+    let mut dead_ends: Vec<G::Cell> = grid
+        .cells()
+        .into_iter()
+        .filter(|cell| grid.passages(*cell).len() == 1)
+        .collect();
+    dead_ends.shuffle(rng);
+    let target = (dead_ends.len() as f32 * ratio.min(1.0)).round() as usize;
+
+    let mut removed = 0;
+    for dead_end in dead_ends {
+        if removed >= target {
+            break;
+        }
+        // A previous dead end might already have been opened into this one.
+        if grid.passages(dead_end).len() != 1 {
+            continue;
+        }
+
+        let closed: Vec<G::Cell> = grid
+            .neighbours(dead_end)
+            .into_iter()
+            .filter(|neighbour| !grid.is_linked(dead_end, *neighbour))
+            .collect();
+        let other_dead_ends: Vec<G::Cell> = closed
+            .iter()
+            .copied()
+            .filter(|neighbour| grid.passages(*neighbour).len() == 1)
+            .collect();
+
+        let candidates = if other_dead_ends.is_empty() {
+            closed
+        } else {
+            other_dead_ends
+        };
+        if let Some(neighbour) = candidates.choose(rng) {
+            if grid.passages(*neighbour).len() == 1 {
+                removed += 1;
+            }
+            grid.link(dead_end, *neighbour);
+            removed += 1;
+        }
+    }
+}
+
+/// Union-find structure, used to know if two cells are already connected.
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(count: usize) -> Self {
+        DisjointSets {
+            parents: (0..count).collect(),
+        }
+    }
+
+    fn find(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        // Path compression, so the next lookups are faster.
+        let mut index = index;
+        while self.parents[index] != root {
+            let parent = self.parents[index];
+            self.parents[index] = root;
+            index = parent;
+        }
+
+        root
+    }
+
+    /// Joins the sets of the two items, returns false if they already were in the same set.
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return false;
+        }
+        self.parents[root_b] = root_a;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::generator::*;
+    use crate::maze::Maze;
+    use rand::SeedableRng;
+
+    #[test]
+    fn braids_a_ratio_of_the_dead_ends() {
+        let count_dead_ends = |maze: &Maze| {
+            maze.cells()
+                .into_iter()
+                .filter(|cell| maze.passages(*cell).len() == 1)
+                .count()
+        };
+
+        for ratio in [0.25, 0.5, 0.75, 1.0] {
+            let mut rng = StdRng::seed_from_u64(7);
+            let mut maze = Maze::blank(20, 20, false);
+            Generator::DepthFirst.generate(&mut maze, &mut rng);
+            let dead_ends = count_dead_ends(&maze);
+
+            braid(&mut maze, ratio, &mut rng);
+            let removed = dead_ends - count_dead_ends(&maze);
+            let target = (dead_ends as f32 * ratio).round() as usize;
+            // Joining two dead ends might remove one more than needed.
+            assert!(
+                (target..=target + 1).contains(&removed),
+                "{} removed instead of {} for {}",
+                removed,
+                target,
+                ratio
+            );
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use rand::{rngs::StdRng, Rng};
+
+use super::weave::{middle, Axis};
+use super::{CellType, Maze, Position};
+
+/// What generators need to know about a maze to carve it, whatever the shape of its cells:
+/// square, hexagonal, triangular or circular.
+pub(super) trait Grid {
+    type Cell: Copy + PartialEq;
+
+    /// Every cell of the grid, in the order they are scanned by the generators.
+    fn cells(&self) -> Vec<Self::Cell>;
+
+    /// Index of a cell, from 0 to `index_count`.
+    fn cell_index(&self, cell: Self::Cell) -> usize;
+
+    /// Number of cell indexes, more than the number of cells when some are left out by a mask.
+    fn index_count(&self) -> usize;
+
+    /// The cell of an index, if it is one of the cells of the grid.
+    fn cell_at(&self, index: usize) -> Option<Self::Cell>;
+
+    fn random_cell(&self, rng: &mut StdRng) -> Self::Cell;
+
+    /// The cells sharing a wall with a cell, whether or not the wall is still there.
+    fn neighbours(&self, cell: Self::Cell) -> Vec<Self::Cell>;
+
+    /// Removes the wall between two neighbours.
+    fn link(&mut self, cell_a: Self::Cell, cell_b: Self::Cell);
+
+    fn is_linked(&self, cell_a: Self::Cell, cell_b: Self::Cell) -> bool;
+
+    /// Marks a cell as part of the maze.
+    fn visit(&mut self, cell: Self::Cell);
+
+    fn is_visited(&self, cell: Self::Cell) -> bool;
+
+    /// Removes the wall between two cells, marking both of them as visited.
+    fn carve(&mut self, cell_a: Self::Cell, cell_b: Self::Cell) {
+        self.link(cell_a, cell_b);
+        self.visit(cell_a);
+        self.visit(cell_b);
+    }
+
+    /// The cells next to a cell, with no wall in between.
+    fn passages(&self, cell: Self::Cell) -> Vec<Self::Cell> {
+        self.neighbours(cell)
+            .into_iter()
+            .filter(|neighbour| self.is_linked(cell, *neighbour))
+            .collect()
+    }
+
+    fn random_unvisited_neighbour(&self, cell: Self::Cell, rng: &mut StdRng) -> Option<Self::Cell> {
+        let unvisited: Vec<Self::Cell> = self
+            .neighbours(cell)
+            .into_iter()
+            .filter(|neighbour| !self.is_visited(*neighbour))
+            .collect();
+
+        match unvisited.len() {
+            0 => None,
+            _ => Some(unvisited[rng.gen_range(0..unvisited.len())]),
+        }
+    }
+}
+
+impl Grid for Maze {
+    type Cell = Position;
+
+    fn cells(&self) -> Vec<Position> {
+        let width = self.width;
+        (0..self.height)
+            .flat_map(move |y| (0..width).map(move |x| Position(x, y)))
+            .filter(|position| self.is_inside(*position))
+            .collect()
+    }
+
+    fn cell_index(&self, position: Position) -> usize {
+        position.1 * self.width + position.0
+    }
+
+    fn index_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn cell_at(&self, index: usize) -> Option<Position> {
+        let position = Position(index % self.width, index / self.width);
+        (index < self.index_count() && self.is_inside(position)).then_some(position)
+    }
+
+    fn random_cell(&self, rng: &mut StdRng) -> Position {
+        // Drawing again until a cell is inside the mask, so the first draw is the same as it
+        // always was without one.
+        loop {
+            let position = Position(rng.gen_range(0..self.width), rng.gen_range(0..self.height));
+            if self.is_inside(position) {
+                return position;
+            }
+        }
+    }
+
+    fn neighbours(&self, position: Position) -> Vec<Position> {
+        let Position(x, y) = position;
+        let mut neighbours: Vec<Position> = Vec::with_capacity(4);
+        if y + 1 < self.height {
+            neighbours.push(Position(x, y + 1));
+        }
+        if y > 0 {
+            neighbours.push(Position(x, y - 1));
+        }
+        if x + 1 < self.width {
+            neighbours.push(Position(x + 1, y));
+        }
+        if x > 0 {
+            neighbours.push(Position(x - 1, y));
+        }
+        neighbours.extend(self.wrapped_neighbours(position));
+        neighbours.retain(|neighbour| self.is_inside(*neighbour));
+        // Crossings only lead along the corridor going over them.
+        if let Some(over) = self.crossing(position) {
+            neighbours.retain(|neighbour| Axis::between(position, *neighbour) == over);
+        }
+
+        // The cells past the corridors that passages go, or could go, under, instead of the
+        // crossings they can't turn into.
+        let mut tunnels = Vec::new();
+        neighbours.retain(|neighbour| match self.tunnel(position, *neighbour) {
+            Some(tunnel) => {
+                tunnels.push(tunnel);
+                self.crossing(*neighbour).is_none()
+            }
+            None => true,
+        });
+        neighbours.extend(tunnels);
+
+        neighbours
+    }
+
+    fn link(&mut self, position_a: Position, position_b: Position) {
+        match middle(position_a, position_b) {
+            Some(_) if !self.is_wrapped_between(position_a, position_b) => {
+                self.cross(position_a, position_b)
+            }
+            _ => self.collapse_wall_between(position_a, position_b),
+        }
+    }
+
+    fn is_linked(&self, position_a: Position, position_b: Position) -> bool {
+        let axis = Axis::between(position_a, position_b);
+        match middle(position_a, position_b) {
+            Some(under) if !self.is_wrapped_between(position_a, position_b) => {
+                self.crossing(under) == Some(axis.across())
+                    && !self.is_wall_between(position_a, under)
+                    && !self.is_wall_between(under, position_b)
+            }
+            _ => {
+                !self.is_wall_between(position_a, position_b)
+                    && [position_a, position_b]
+                        .iter()
+                        .all(|cell| self.crossing(*cell).unwrap_or(axis) == axis)
+            }
+        }
+    }
+
+    fn visit(&mut self, position: Position) {
+        if let Some(mut cell) = self.get_floor_cell(position) {
+            cell.visited = true;
+            // As the maze is initialized with only walls, it's important to mark cells as floor.
+            if cell.cell_type == CellType::Wall {
+                cell.cell_type = CellType::Floor;
+            }
+            self.set_floor_cell(position, cell);
+        }
+    }
+
+    fn is_visited(&self, position: Position) -> bool {
+        self.get_floor_cell(position)
+            .map(|cell| cell.visited)
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,558 @@
+use std::f32::consts::SQRT_2;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use skia_safe::{Canvas, Paint, PaintStyle, Path, Point, Rect};
+
+use super::grid::Grid;
+use super::solver::Graph;
+use super::{
+    Cell, CellType, Maze, MazeOptions, Overlay, Placement, Position, Shape, Solution, Statistics,
+    Style, Wrap, MAX_CELLS, MAZE_BORDER, MAZE_TO_PIXEL, STROKE_WIDTH,
+};
+use crate::utils::{Bounded, Palette};
+
+/// Space between the floor plans drawn side by side, in cells of the full grid.
+const PLAN_SPACING: f32 = 4.0;
+/// How far above the level below a level is drawn in an isometric stack, as a share of the
+/// height of its floor plate.
+const LEVEL_RISE: f32 = 0.6;
+
+/// How the levels of a maze are drawn.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    /// The floor plans of the levels next to each other, the ground level on the left.
+    SideBySide,
+    /// The levels stacked as floor plates seen from above, the ground level at the bottom.
+    Isometric,
+}
+
+impl Projection {
+    pub const ALL: [Projection; 2] = [Projection::SideBySide, Projection::Isometric];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::SideBySide => "side-by-side",
+            Projection::Isometric => "isometric",
+        }
+    }
+}
+
+impl Display for Projection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Projection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Projection::ALL
+            .iter()
+            .find(|projection| projection.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Projection::ALL.iter().map(|p| p.name()).collect();
+                format!(
+                    "ERROR: unknown projection `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// A cell of a maze of several levels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) struct Location {
+    /// From 0 for the ground level, up.
+    pub(super) level: usize,
+    pub(super) position: Position,
+}
+
+/// A square maze of several levels, each cell leading to the cells right above and below it by
+/// stairs. Each level is a square maze of its own, the stairs being kept aside.
+pub(super) struct LayeredMaze {
+    levels: Vec<Maze>,
+    /// Whether stairs go up from each cell, by index of the cell.
+    stairs: Vec<bool>,
+    /// A cell on the west side of the ground level.
+    entry: Location,
+    /// A cell on the east side of the top level.
+    exit: Location,
+    pub(super) solution: Option<Solution<Location>>,
+}
+
+impl LayeredMaze {
+    /// Builds the maze drawn on a canvas, its levels side by side holding as many cells as the
+    /// maze of the original painting would.
+    pub(super) fn fit(canvas: &Canvas, options: &MazeOptions) -> Result<Self, String> {
+        let count = options.levels as f32;
+        let spacing = PLAN_SPACING * MAZE_TO_PIXEL * (count - 1.0);
+        let (width, height) = options.cells.unwrap_or((
+            ((canvas.width() - MAZE_BORDER * 2.0 - spacing) / count / MAZE_TO_PIXEL) as usize,
+            ((canvas.height() - MAZE_BORDER * 2.0) / MAZE_TO_PIXEL) as usize,
+        ));
+        LayeredMaze::new(width, height, options)
+    }
+
+    pub(super) fn new(width: usize, height: usize, options: &MazeOptions) -> Result<Self, String> {
+        if options.shape != Shape::Square {
+            return Err("ERROR: only square mazes can have several levels".to_string());
+        }
+        if options.mask.is_some() {
+            return Err(
+                "ERROR: mazes of several levels can't follow the shape of a mask".to_string(),
+            );
+        }
+        if options.weave {
+            return Err("ERROR: mazes of several levels can't weave their passages".to_string());
+        }
+        if options.wrap != Wrap::None {
+            return Err("ERROR: mazes of several levels can't wrap around".to_string());
+        }
+        if options.placement != Placement::default() {
+            return Err(
+                "ERROR: mazes of several levels can't place their entry and exits".to_string(),
+            );
+        }
+        if options.generator.is_rectangular_only() {
+            return Err(format!(
+                "ERROR: the {} generator can't carve mazes of several levels",
+                options.generator
+            ));
+        }
+        if options.levels == 0 || width == 0 || height == 0 {
+            return Err(format!(
+                "ERROR: {} levels of {}x{} cells can't hold a maze",
+                options.levels, width, height
+            ));
+        }
+        let cells = width
+            .checked_mul(height)
+            .and_then(|cells| cells.checked_mul(options.levels));
+        if cells.is_none_or(|cells| cells > MAX_CELLS) {
+            return Err(format!(
+                "ERROR: a maze can have at most {} cells, not {} levels of {}x{}",
+                MAX_CELLS, options.levels, width, height
+            ));
+        }
+
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        let top = options.levels - 1;
+        let mut maze = LayeredMaze {
+            levels: (0..options.levels)
+                .map(|_| Maze::blank(width, height, false))
+                .collect(),
+            stairs: vec![false; options.levels * width * height],
+            entry: Location {
+                level: 0,
+                position: Position(0, 0),
+            },
+            exit: Location {
+                level: top,
+                position: Position(width - 1, 0),
+            },
+            solution: None,
+        };
+
+        options.generator.generate_on_grid(&mut maze, &mut rng);
+        super::generator::braid(&mut maze, options.braid, &mut rng);
+
+        // Entering the ground level from the west, and leaving the top level to the east.
+        maze.entry.position.1 = rng.gen_range(0..height);
+        maze.exit.position.1 = rng.gen_range(0..height);
+        let doors = [
+            (0, 0, maze.entry.position.1),
+            (top, width * 2, maze.exit.position.1),
+        ];
+        for (level, true_x, y) in doors {
+            let cell = Cell {
+                visited: false,
+                cell_type: CellType::Floor,
+            };
+            maze.levels[level].set_any_cell(true_x, y * 2 + 1, cell);
+        }
+        maze.solution = options
+            .effective_solver()
+            .solve(&maze, maze.entry, maze.exit);
+
+        Ok(maze)
+    }
+
+    pub(super) fn statistics(&self) -> Statistics {
+        let path = self
+            .solution
+            .as_ref()
+            .map(|solution| solution.path.as_slice());
+        Statistics::measure(self, path.unwrap_or_default())
+    }
+
+    /// Index of the cell the stairs between two cells go up from, when one is right above the
+    /// other.
+    fn stairs_between(&self, a: Location, b: Location) -> Option<usize> {
+        match (a.position == b.position, a.level.abs_diff(b.level)) {
+            (true, 1) => Some(self.cell_index(if a.level < b.level { a } else { b })),
+            _ => None,
+        }
+    }
+
+    fn has_stairs_up(&self, location: Location) -> bool {
+        location.level + 1 < self.levels.len() && self.stairs[self.cell_index(location)]
+    }
+
+    /// Size of the floor plan of a level, in cells of the full grid.
+    fn plan_size(&self) -> (f32, f32) {
+        let level = &self.levels[0];
+        ((level.width * 2 + 1) as f32, (level.height * 2 + 1) as f32)
+    }
+
+    /// Top left corner of the floor plan of a level, in cells of the full grid.
+    fn corner(&self, projection: Projection, level: usize) -> Point {
+        let (width, height) = self.plan_size();
+        match projection {
+            Projection::SideBySide => Point::new(level as f32 * (width + PLAN_SPACING), 0.0),
+            Projection::Isometric => {
+                // Moving as much along both axes of the plan raises it straight up once
+                // projected.
+                let rise = level as f32 * (width + height) / 2.0 * LEVEL_RISE;
+                Point::new(-rise, -rise)
+            }
+        }
+    }
+
+    /// Center of a cell on the floor plan of its level, as drawn by `Maze::draw_in`.
+    fn center(&self, projection: Projection, location: Location) -> Point {
+        let Position(x, y) = location.position;
+        self.corner(projection, location.level)
+            + Point::new(x as f32 * 2.0 + 1.5, y as f32 * 2.0 + 1.5)
+    }
+
+    /// Draws the floor plan of each level, with the stairs going up and down, and the solution
+    /// going through them.
+    pub(super) fn draw(&self, canvas: &mut Canvas, projection: Projection, style: &Style) {
+        let (width, height) = self.plan_size();
+        let count = self.levels.len() as f32;
+        // Size of the drawing before scaling it, and where the plan of the ground level starts.
+        let (size, origin) = match projection {
+            Projection::SideBySide => (
+                (count * width + (count - 1.0) * PLAN_SPACING, height),
+                (0.0, 0.0),
+            ),
+            Projection::Isometric => {
+                let rise = -self.corner(projection, self.levels.len() - 1).y / SQRT_2;
+                (
+                    (
+                        (width + height) / SQRT_2,
+                        (width + height) / SQRT_2 / 2.0 + rise,
+                    ),
+                    (height / SQRT_2, rise),
+                )
+            }
+        };
+        let scale = ((canvas.width() - MAZE_BORDER * 2.0) / size.0)
+            .min((canvas.height() - MAZE_BORDER * 2.0) / size.1);
+
+        canvas.save();
+        canvas.translate((
+            (canvas.width() - size.0 * scale) / 2.0,
+            (canvas.height() - size.1 * scale) / 2.0,
+        ));
+        canvas.scale((scale, scale));
+        canvas.translate(origin);
+        if projection == Projection::Isometric {
+            canvas.scale((1.0, 0.5));
+            canvas.rotate(45.0, None);
+        }
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        for (level, maze) in self.levels.iter().enumerate() {
+            let corner = self.corner(projection, level);
+            let plate = Rect::from_xywh(corner.x, corner.y, width, height);
+            if projection == Projection::Isometric {
+                // Hiding the levels below.
+                paint.set_style(PaintStyle::Fill);
+                paint.set_color(Palette::BEIGE);
+                canvas.draw_rect(plate, &paint);
+                paint.set_style(PaintStyle::Stroke);
+                paint.set_stroke_width(STROKE_WIDTH);
+                paint.set_color(Palette::DARK_BEIGE);
+                canvas.draw_rect(plate, &paint);
+            }
+
+            // The levels have no solution of their own.
+            maze.draw_in(canvas, plate, Overlay::Solution, style);
+            self.draw_stairs(canvas, projection, level);
+            self.draw_solution(canvas, projection, level);
+        }
+
+        canvas.restore();
+    }
+
+    /// A triangle pointing north for the stairs going up from a cell, and one pointing south
+    /// for the stairs going down.
+    fn draw_stairs(&self, canvas: &mut Canvas, projection: Projection, level: usize) {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_color(Palette::GRAY);
+
+        let mut path = Path::new();
+        for position in self.levels[level].cells() {
+            let location = Location { level, position };
+            let center = self.center(projection, location);
+            let below = Location {
+                level: level.wrapping_sub(1),
+                position,
+            };
+            let directions = [
+                (self.has_stairs_up(location), -1.0),
+                (level > 0 && self.has_stairs_up(below), 1.0),
+            ];
+            for (_, direction) in directions.iter().filter(|(stairs, _)| *stairs) {
+                path.move_to(center + Point::new(-0.5, direction * 0.1));
+                path.line_to(center + Point::new(0.5, direction * 0.1));
+                path.line_to(center + Point::new(0.0, direction * 0.6));
+                path.close();
+            }
+        }
+        canvas.draw_path(&path, &paint);
+    }
+
+    /// The part of the solution on a level, with the stairs coming to it from the level below
+    /// when they are stacked.
+    fn draw_solution(&self, canvas: &mut Canvas, projection: Projection, level: usize) {
+        let solution = match &self.solution {
+            Some(solution) => solution,
+            None => return,
+        };
+
+        // Coming from the west of the entry, and leaving to the east of the exit.
+        let mut points = vec![(
+            self.entry.level,
+            self.center(projection, self.entry) + Point::new(-2.0, 0.0),
+        )];
+        points.extend(
+            solution
+                .path
+                .iter()
+                .map(|location| (location.level, self.center(projection, *location))),
+        );
+        points.push((
+            self.exit.level,
+            self.center(projection, self.exit) + Point::new(2.0, 0.0),
+        ));
+
+        let mut path = Path::new();
+        let mut last = None;
+        for step in points.windows(2) {
+            let ((from_level, from), (to_level, to)) = (step[0], step[1]);
+            let is_shown = match projection {
+                Projection::SideBySide => from_level == level && to_level == level,
+                Projection::Isometric => from_level.max(to_level) == level,
+            };
+            if is_shown {
+                if last != Some(from) {
+                    path.move_to(from);
+                }
+                path.line_to(to);
+                last = Some(to);
+            }
+        }
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(STROKE_WIDTH);
+        paint.set_color(Palette::RED);
+        canvas.draw_path(&path, &paint);
+    }
+}
+
+impl Grid for LayeredMaze {
+    type Cell = Location;
+
+    fn cells(&self) -> Vec<Location> {
+        self.levels
+            .iter()
+            .enumerate()
+            .flat_map(|(level, maze)| {
+                maze.cells()
+                    .into_iter()
+                    .map(move |position| Location { level, position })
+            })
+            .collect()
+    }
+
+    fn cell_index(&self, location: Location) -> usize {
+        let level = &self.levels[location.level];
+        location.level * level.index_count() + level.cell_index(location.position)
+    }
+
+    fn index_count(&self) -> usize {
+        self.levels.len() * self.levels[0].index_count()
+    }
+
+    fn cell_at(&self, index: usize) -> Option<Location> {
+        let per_level = self.levels[0].index_count();
+        let level = index / per_level;
+        let position = self.levels.get(level)?.cell_at(index % per_level)?;
+        Some(Location { level, position })
+    }
+
+    fn random_cell(&self, rng: &mut StdRng) -> Location {
+        let level = rng.gen_range(0..self.levels.len());
+        Location {
+            level,
+            position: self.levels[level].random_cell(rng),
+        }
+    }
+
+    fn neighbours(&self, location: Location) -> Vec<Location> {
+        let Location { level, position } = location;
+        let mut neighbours: Vec<Location> = self.levels[level]
+            .neighbours(position)
+            .into_iter()
+            .map(|position| Location { level, position })
+            .collect();
+        if level > 0 {
+            neighbours.push(Location {
+                level: level - 1,
+                position,
+            });
+        }
+        if level + 1 < self.levels.len() {
+            neighbours.push(Location {
+                level: level + 1,
+                position,
+            });
+        }
+
+        neighbours
+    }
+
+    fn link(&mut self, location_a: Location, location_b: Location) {
+        match self.stairs_between(location_a, location_b) {
+            Some(index) => self.stairs[index] = true,
+            None => self.levels[location_a.level].link(location_a.position, location_b.position),
+        }
+    }
+
+    fn is_linked(&self, location_a: Location, location_b: Location) -> bool {
+        match self.stairs_between(location_a, location_b) {
+            Some(index) => self.stairs[index],
+            None => {
+                location_a.level == location_b.level
+                    && self.levels[location_a.level]
+                        .is_linked(location_a.position, location_b.position)
+            }
+        }
+    }
+
+    fn visit(&mut self, location: Location) {
+        self.levels[location.level].visit(location.position);
+    }
+
+    fn is_visited(&self, location: Location) -> bool {
+        self.levels[location.level].is_visited(location.position)
+    }
+}
+
+impl Graph for LayeredMaze {
+    type Node = Location;
+
+    fn node_count(&self) -> usize {
+        self.index_count()
+    }
+
+    fn index(&self, location: Location) -> usize {
+        self.cell_index(location)
+    }
+
+    fn node(&self, index: usize) -> Option<Location> {
+        self.cell_at(index)
+    }
+
+    fn edges(&self, location: Location) -> Vec<Location> {
+        self.passages(location)
+    }
+
+    fn nodes(&self) -> Vec<Location> {
+        self.cells()
+    }
+
+    fn estimate(&self, from: Location, to: Location) -> usize {
+        let (Position(from_x, from_y), Position(to_x, to_y)) = (from.position, to.position);
+        from.level.abs_diff(to.level) + from_x.abs_diff(to_x) + from_y.abs_diff(to_y)
+    }
+}
+
+impl Display for LayeredMaze {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Maze of {} levels of dimension {}x{}, {} stairs",
+            self.levels.len(),
+            self.levels[0].width,
+            self.levels[0].height,
+            self.stairs.iter().filter(|stairs| **stairs).count()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::levels::*;
+    use crate::maze::{Generator, Solver};
+
+    #[test]
+    fn climbs_through_the_levels() {
+        for generator in [
+            Generator::DepthFirst,
+            Generator::Prim,
+            Generator::Wilson,
+            Generator::HuntAndKill,
+        ] {
+            let options = MazeOptions {
+                generator,
+                levels: 3,
+                solver: Solver::AStar,
+                ..MazeOptions::default()
+            };
+            let maze = LayeredMaze::new(8, 6, &options).unwrap();
+
+            // A perfect maze, every cell being reachable through a single path.
+            let cells = maze.cells();
+            let passages: usize = cells.iter().map(|cell| maze.passages(*cell).len()).sum();
+            assert_eq!(passages / 2, cells.len() - 1);
+            let mut seen = vec![false; maze.index_count()];
+            let mut stack = vec![maze.entry];
+            while let Some(cell) = stack.pop() {
+                if !seen[maze.cell_index(cell)] {
+                    seen[maze.cell_index(cell)] = true;
+                    stack.extend(maze.passages(cell));
+                }
+            }
+            assert!(seen.into_iter().all(|seen| seen));
+
+            // The solution climbs the stairs from the ground level to the top one.
+            let path = &maze.solution.as_ref().unwrap().path;
+            assert_eq!(path.first(), Some(&maze.entry));
+            assert_eq!(path.last(), Some(&maze.exit));
+            assert_eq!((maze.entry.level, maze.exit.level), (0, 2));
+            for step in path.windows(2) {
+                assert!(maze.neighbours(step[0]).contains(&step[1]));
+                assert!(maze.is_linked(step[0], step[1]));
+            }
+        }
+
+        let options = MazeOptions {
+            generator: Generator::Eller,
+            levels: 2,
+            ..MazeOptions::default()
+        };
+        assert!(LayeredMaze::new(8, 6, &options).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+
+use skia_safe::{
+    utils::parse_path, AlphaType, CachingHint, Canvas, ColorType, Data, Font, Image, ImageInfo,
+    Paint, Path, Rect, Surface, Typeface,
+};
+
+use crate::utils::Palette;
+
+/// A shape to restrict a square maze to, like a logo or a word: only the cells covered by the
+/// mask are part of the maze.
+#[derive(Clone, Debug)]
+pub enum Mask {
+    /// A black and white picture, the dark pixels being the inside of the mask.
+    Image(Image),
+    /// A filled path.
+    Path(Path),
+    /// A text written with the default font.
+    Text(String),
+}
+
+impl Mask {
+    pub fn from_png(bytes: &[u8]) -> Result<Self, String> {
+        Image::from_encoded(Data::new_copy(bytes))
+            .map(Mask::Image)
+            .ok_or_else(|| "ERROR: couldn't decode the mask image".to_string())
+    }
+
+    /// Reads the path from SVG path data, like the `d` attribute of a `<path>`.
+    pub fn from_svg_path(svg: &str) -> Result<Self, String> {
+        parse_path::from_svg(svg)
+            .filter(|path| !path.bounds().is_empty())
+            .map(Mask::Path)
+            .ok_or_else(|| format!("ERROR: `{}` is not a valid SVG path", svg))
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        if text.trim().is_empty() {
+            return Err("ERROR: the mask text is empty".to_string());
+        }
+
+        Ok(Mask::Text(text.to_string()))
+    }
+
+    /// Which cells of a maze of `width` by `height` cells are inside the mask, drawing the mask
+    /// in black on a white picture of one pixel per cell, as large as it fits.
+    pub(super) fn cells(&self, width: usize, height: usize) -> Result<Vec<bool>, String> {
+        let mut surface = Surface::new_raster_n32_premul((width as i32, height as i32))
+            .expect("No SKIA surface available.");
+        let canvas = surface.canvas();
+        canvas.clear(Palette::WHITE);
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_color(Palette::BLACK);
+
+        match self {
+            Mask::Image(image) => {
+                let bounds = Rect::from_wh(image.width() as f32, image.height() as f32);
+                fit(canvas, bounds, width, height);
+                canvas.draw_image(image, (0.0, 0.0), Some(&paint));
+            }
+            Mask::Path(path) => {
+                fit(canvas, *path.bounds(), width, height);
+                canvas.draw_path(path, &paint);
+            }
+            Mask::Text(text) => {
+                let font = Font::new(Typeface::default(), 64.0);
+                let (_, bounds) = font.measure_str(text, Some(&paint));
+                fit(canvas, bounds, width, height);
+                canvas.draw_str(text, (0.0, 0.0), &font, &paint);
+            }
+        }
+
+        let info = ImageInfo::new(
+            (width as i32, height as i32),
+            ColorType::Gray8,
+            AlphaType::Opaque,
+            None,
+        );
+        let mut pixels = vec![0u8; width * height];
+        if !surface.image_snapshot().read_pixels(
+            &info,
+            &mut pixels,
+            width,
+            (0, 0),
+            CachingHint::Allow,
+        ) {
+            return Err("ERROR: couldn't read the mask pixels".to_string());
+        }
+
+        Ok(pixels.into_iter().map(|pixel| pixel < 128).collect())
+    }
+}
+
+/// Scales and centers the canvas so `bounds` fill as much as possible of `width` by `height`.
+fn fit(canvas: &mut Canvas, bounds: Rect, width: usize, height: usize) {
+    let (width, height) = (width as f32, height as f32);
+    let scale = (width / bounds.width()).min(height / bounds.height());
+
+    canvas.translate((
+        (width - bounds.width() * scale) / 2.0,
+        (height - bounds.height() * scale) / 2.0,
+    ));
+    canvas.scale((scale, scale));
+    canvas.translate((-bounds.left, -bounds.top));
+}
+
+/// Joins the separate parts of a mask, like the letters of a word, with lines of cells, so a
+/// single maze goes through all of them. Fails if no cell is inside.
+pub(super) fn join_regions(width: usize, height: usize, inside: &mut [bool]) -> Result<(), String> {
+    let first = inside
+        .iter()
+        .position(|inside| *inside)
+        .ok_or_else(|| "ERROR: no cell of the maze is inside the mask".to_string())?;
+
+    let neighbours = |cell: usize| {
+        let (x, y) = (cell % width, cell / width);
+        let mut neighbours = Vec::with_capacity(4);
+        if y + 1 < height {
+            neighbours.push(cell + width);
+        }
+        if y > 0 {
+            neighbours.push(cell - width);
+        }
+        if x + 1 < width {
+            neighbours.push(cell + 1);
+        }
+        if x > 0 {
+            neighbours.push(cell - 1);
+        }
+        neighbours
+    };
+
+    loop {
+        // The part of the mask holding the first cell, growing as the other parts get joined.
+        let mut region = vec![false; inside.len()];
+        let mut stack = vec![first];
+        while let Some(cell) = stack.pop() {
+            if !region[cell] {
+                region[cell] = true;
+                stack.extend(neighbours(cell).into_iter().filter(|n| inside[*n]));
+            }
+        }
+
+        // Walks outside of the region until the closest cell of another part.
+        let mut previous: Vec<Option<usize>> = vec![None; inside.len()];
+        let mut seen = region.clone();
+        let mut queue: VecDeque<usize> = (0..inside.len()).filter(|cell| region[*cell]).collect();
+        let mut reached = None;
+        'search: while let Some(cell) = queue.pop_front() {
+            for neighbour in neighbours(cell) {
+                if seen[neighbour] {
+                    continue;
+                }
+                seen[neighbour] = true;
+                previous[neighbour] = Some(cell);
+                if inside[neighbour] {
+                    reached = Some(neighbour);
+                    break 'search;
+                }
+                queue.push_back(neighbour);
+            }
+        }
+
+        match reached {
+            Some(mut cell) => {
+                while let Some(before) = previous[cell] {
+                    inside[before] = true;
+                    cell = before;
+                }
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::mask::*;
+    use crate::maze::{CellType, Generator, Maze, MazeOptions, Position};
+
+    #[test]
+    fn joins_separate_parts() {
+        // Two blocks, three cells apart.
+        let (width, height) = (7, 3);
+        let mut inside: Vec<bool> = (0..width * height)
+            .map(|cell| {
+                let x = cell % width;
+                !(2..=4).contains(&x)
+            })
+            .collect();
+        join_regions(width, height, &mut inside).unwrap();
+
+        let joined = (0..height).any(|y| (2..5).all(|x| inside[y * width + x]));
+        assert!(joined);
+        assert_eq!(inside.iter().filter(|inside| **inside).count(), 12 + 3);
+    }
+
+    #[test]
+    fn rejects_empty_mask() {
+        let mut inside = vec![false; 12];
+        assert!(join_regions(4, 3, &mut inside).is_err());
+        assert!(Mask::from_text("  ").is_err());
+    }
+
+    #[test]
+    fn keeps_the_maze_inside_the_mask() {
+        // A diamond, with a separate block in the corner.
+        let (width, height) = (12, 8);
+        let inside: Vec<bool> = (0..width * height)
+            .map(|cell| {
+                let (x, y) = ((cell % width) as isize, (cell / width) as isize);
+                (x - 6).abs() + (y - 4).abs() < 4 || (x < 2 && y < 2)
+            })
+            .collect();
+        let mut joined = inside.clone();
+        join_regions(width, height, &mut joined).unwrap();
+
+        for generator in [Generator::DepthFirst, Generator::Wilson, Generator::Kruskal] {
+            let options = MazeOptions {
+                generator,
+                ..MazeOptions::default()
+            };
+            let maze = Maze::with_cells(width, height, Some(inside.clone()), &options).unwrap();
+
+            for (index, inside) in joined.iter().enumerate() {
+                let position = Position(index % width, index / width);
+                let cell_type = maze.get_floor_cell(position).unwrap().cell_type;
+                if *inside {
+                    assert!(cell_type == CellType::Floor);
+                } else {
+                    assert!(cell_type != CellType::Floor);
+                }
+            }
+
+            let solution = maze.solution.as_ref().unwrap();
+            let doors = maze.doors.as_ref().unwrap();
+            assert_eq!(solution.path.first(), Some(&doors.entry));
+            assert_eq!(solution.path.last(), Some(&doors.exits[0]));
+        }
+    }
+
+    #[test]
+    fn rejects_row_based_generators() {
+        let options = MazeOptions {
+            generator: Generator::BinaryTree,
+            ..MazeOptions::default()
+        };
+        assert!(Maze::with_cells(4, 3, Some(vec![true; 12]), &options).is_err());
+    }
+}
@@ -0,0 +1,854 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use skia_safe::{Canvas, Paint, PaintStyle, Path, Point, Rect};
+
+use crate::geometry::Segment;
+use crate::utils::{Bounded, Drawable, Palette};
+
+mod animation;
+mod book;
+mod doors;
+mod format;
+mod generator;
+mod grid;
+mod levels;
+mod mask;
+mod packed;
+mod player;
+mod shape;
+mod solver;
+mod statistics;
+mod style;
+mod validation;
+mod weave;
+mod wrap;
+
+pub use animation::{Animation, MazeAnimation};
+pub use book::{Book, Paper};
+pub use doors::{Door, Placement};
+pub use generator::Generator;
+pub use levels::Projection;
+pub use mask::Mask;
+pub use player::{Move, Player};
+pub use shape::Shape;
+pub use solver::{Solution, Solver};
+pub use statistics::Statistics;
+pub use style::{Style, Walls};
+pub use wrap::Wrap;
+
+use doors::Doors;
+use grid::Grid;
+use levels::LayeredMaze;
+use packed::PackedCells;
+use shape::{Layout, ShapedMaze};
+use weave::Axis;
+
+/// The higher the number, the less complex the maze.
+const MAZE_TO_PIXEL: f32 = 10.0;
+/// Adjust border to frame the maze in a nice way.
+const MAZE_BORDER: f32 = 40.0;
+/// Adjust for a wider or thinner solution, and walls of other shapes.
+const STROKE_WIDTH: f32 = 0.5;
+/// Space between the walls of a passage going under a bridge and the walls of the bridge.
+const BRIDGE_GAP: f32 = 0.5;
+/// How many walls are drawn at once, so huge mazes don't keep a path of all of their walls.
+const WALLS_PER_PATH: usize = 100_000;
+/// How close to the target difficulty a seed search stops.
+const DIFFICULTY_TOLERANCE: f32 = 1.0;
+/// The most cells of a maze, so the depth-first generator can keep their indexes as `u32`.
+const MAX_CELLS: usize = u32::MAX as usize;
+
+#[derive(Copy, Clone)]
+struct Cell {
+    visited: bool,
+    cell_type: CellType,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Position(usize, usize);
+
+struct Wall(Position, Position);
+
+impl From<(usize, usize)> for Position {
+    fn from(point: (usize, usize)) -> Self {
+        Position(point.0, point.1)
+    }
+}
+
+impl Wall {
+    fn as_segment(&self, half_stroke: f32) -> Option<Segment> {
+        let Wall(a, b) = self;
+
+        if a.0 != b.0 {
+            // Horizontal wall
+            Some(Segment::new(
+                a.0 as f32 - half_stroke,
+                a.1 as f32,
+                b.0 as f32 + half_stroke,
+                b.1 as f32,
+            ))
+        } else if a.1 != b.1 {
+            // Vertical wall
+            Some(Segment::new(
+                a.0 as f32,
+                a.1 as f32 - half_stroke,
+                b.0 as f32,
+                b.1 as f32 + half_stroke,
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum CellType {
+    Wall,
+    Floor,
+    /// Left out of the maze by its mask, neither a wall nor a floor.
+    Outside,
+    /// A floor where a corridor goes over another one, with the axis of the one going over.
+    Crossing(Axis),
+}
+
+/// Maze structure. Can you get out?
+///
+/// A maze or labyrinth of size 3 * 3 might generate the following structure
+///
+/// ```text
+/// # # # # # # #
+/// #       #   #
+/// #   # # #   #
+/// #       #   #
+/// # # #   #   #
+/// #           #
+/// # # # # # # #
+/// ```
+#[derive(Clone)]
+pub struct Maze {
+    width: usize,
+    height: usize,
+    data: PackedCells,
+    doors: Option<Doors>,
+    solution: Option<Solution>,
+    /// Whether generators can carve passages under straight corridors.
+    weave: bool,
+    wrap: Wrap,
+}
+
+impl Maze {
+    /// Builds the square maze drawn on a canvas, as many cells fitting in it as in the original
+    /// painting. Wrapped mazes fill the canvas between their wrapped sides, so it tiles. Other
+    /// shapes are ignored.
+    pub fn fit(canvas: &Canvas, options: &MazeOptions) -> Result<Self, String> {
+        let (width, height) = fit_size(canvas, options);
+        Maze::new(width, height, options)
+    }
+
+    /// A maze of `width` by `height` cells.
+    pub fn new(width: usize, height: usize, options: &MazeOptions) -> Result<Self, String> {
+        let inside = inside_cells(width, height, options)?;
+
+        Maze::with_cells(width, height, inside, options)
+    }
+
+    /// Builds a maze restricted to the cells marked as inside, if any, ignoring the mask of the
+    /// options.
+    fn with_cells(
+        width: usize,
+        height: usize,
+        inside: Option<Vec<bool>>,
+        options: &MazeOptions,
+    ) -> Result<Self, String> {
+        let mut rng = StdRng::seed_from_u64(options.seed);
+
+        Ok(Maze::prepare(width, height, inside, options)?.build(options, &mut rng))
+    }
+
+    /// A maze of only walls, restricted to the cells marked as inside if any, once the options
+    /// are checked.
+    fn prepare(
+        width: usize,
+        height: usize,
+        inside: Option<Vec<bool>>,
+        options: &MazeOptions,
+    ) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err(format!(
+                "ERROR: a maze needs at least a cell, not {}x{}",
+                width, height
+            ));
+        }
+        if width
+            .checked_mul(height)
+            .is_none_or(|cells| cells > MAX_CELLS)
+        {
+            return Err(format!(
+                "ERROR: a maze can have at most {} cells, not {}x{}",
+                MAX_CELLS, width, height
+            ));
+        }
+        let mut maze = Maze {
+            wrap: options.wrap,
+            ..Maze::blank(width, height, options.weave)
+        };
+        maze.check_wrap(inside.is_some(), options)?;
+        if options.weave && !options.generator.can_weave() {
+            return Err(format!(
+                "ERROR: the {} generator can't weave passages",
+                options.generator
+            ));
+        }
+        if let Some(mut inside) = inside {
+            if options.generator.is_rectangular_only() {
+                return Err(format!(
+                    "ERROR: the {} generator can't follow the shape of a mask",
+                    options.generator
+                ));
+            }
+            mask::join_regions(width, height, &mut inside)?;
+            maze.leave_out(&inside);
+        }
+
+        Ok(maze)
+    }
+
+    /// Carves the passages of a prepared maze, then opens its doors and solves it.
+    fn build(self, options: &MazeOptions, rng: &mut StdRng) -> Self {
+        self.initialise_maze(options.generator, rng)
+            .braid(options.braid, rng)
+            .open_doors(&options.placement, rng)
+            .solve(options.effective_solver())
+    }
+
+    /// A maze of only walls, for the generators to carve.
+    fn blank(width: usize, height: usize, weave: bool) -> Self {
+        Maze {
+            width,
+            height,
+            data: PackedCells::new((width * 2 + 1) * (height * 2 + 1)),
+            doors: None,
+            solution: None,
+            weave,
+            wrap: Wrap::None,
+        }
+    }
+
+    /// Marks the cells that are not inside, and the walls only surrounded by them, as outside
+    /// of the maze.
+    fn leave_out(&mut self, inside: &[bool]) {
+        let is_inside =
+            |x: usize, y: usize| x < self.width && y < self.height && inside[y * self.width + x];
+        // The cells around a cell of the full grid, in cell coordinates shifted by one so the
+        // ones before the first row or column are still positive.
+        let around = |true_position: usize| {
+            if true_position % 2 == 1 {
+                vec![true_position / 2 + 1]
+            } else {
+                vec![true_position / 2, true_position / 2 + 1]
+            }
+        };
+
+        let width = self.width * 2 + 1;
+        let outside: Vec<usize> = (0..self.data.len())
+            .filter(|index| {
+                let (true_x, true_y) = (index % width, index / width);
+                around(true_y).into_iter().all(|y| {
+                    around(true_x)
+                        .into_iter()
+                        .all(|x| x == 0 || y == 0 || !is_inside(x - 1, y - 1))
+                })
+            })
+            .collect();
+
+        for index in outside {
+            self.data.set_type(index, CellType::Outside);
+        }
+    }
+
+    /// Whether a cell is part of the maze, and not left out by its mask.
+    fn is_inside(&self, position: Position) -> bool {
+        self.get_floor_cell(position)
+            .map(|cell| cell.cell_type != CellType::Outside)
+            .unwrap_or(false)
+    }
+
+    fn initialise_maze(mut self, generator: Generator, rng: &mut StdRng) -> Self {
+        generator.generate(&mut self, rng);
+        self
+    }
+
+    fn braid(mut self, ratio: f32, rng: &mut StdRng) -> Self {
+        generator::braid(&mut self, ratio, rng);
+        self
+    }
+
+    fn get_floor_cell(&self, position: Position) -> Option<Cell> {
+        let Position(x, y) = position;
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        self.get_any_cell(x * 2 + 1, y * 2 + 1)
+    }
+
+    fn set_floor_cell(&mut self, position: Position, cell: Cell) {
+        let Position(x, y) = position;
+        if x < self.width && y < self.height {
+            self.set_any_cell(x * 2 + 1, y * 2 + 1, cell);
+        }
+    }
+
+    fn get_any_cell(&self, true_x: usize, true_y: usize) -> Option<Cell> {
+        if true_x > self.width as usize * 2 || true_y > self.height as usize * 2 {
+            return None;
+        }
+
+        self.data
+            .get(true_y * (self.width as usize * 2 + 1) + true_x)
+    }
+
+    fn set_any_cell(&mut self, true_x: usize, true_y: usize, cell: Cell) {
+        if true_x <= self.width * 2 && true_y <= self.height * 2 {
+            self.data.set(true_y * (self.width * 2 + 1) + true_x, cell);
+        }
+    }
+
+    fn collapse_wall_between(&mut self, position_a: Position, position_b: Position) {
+        self.set_wall_between(position_a, position_b, CellType::Floor);
+    }
+
+    fn raise_wall_between(&mut self, position_a: Position, position_b: Position) {
+        self.set_wall_between(position_a, position_b, CellType::Wall);
+    }
+
+    fn set_wall_between(
+        &mut self,
+        position_a: Position,
+        position_b: Position,
+        cell_type: CellType,
+    ) {
+        let (x, y) = self.wall_between(position_a, position_b);
+        let index = y * (self.width as usize * 2 + 1) + x;
+
+        self.data.set_type(index, cell_type);
+        // Copying the walls of the wrapped sides on the opposite side.
+        if self.wrap.horizontal() && x == self.width * 2 {
+            self.data.set_type(index - x, cell_type);
+        }
+        if self.wrap.vertical() && y == self.height * 2 {
+            self.data.set_type(x, cell_type);
+        }
+    }
+
+    /// Marks every cell as floor, for generators that only deal with the walls in between.
+    fn open_all_floor_cells(&mut self) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.visit(Position(x, y));
+            }
+        }
+    }
+
+    fn is_wall_between(&self, position_a: Position, position_b: Position) -> bool {
+        let (x, y) = self.wall_between(position_a, position_b);
+
+        match self.get_any_cell(x, y) {
+            Some(cell) => cell.cell_type == CellType::Wall,
+            None => true,
+        }
+    }
+
+    /// Size of the full grid as drawn, in cells. The walls of a wrapped side and of the opposite
+    /// one are the same, half of them being drawn on each side.
+    fn grid_size(&self) -> (f32, f32) {
+        let size = |cells: usize, wraps: bool| match wraps {
+            true => cells as f32 * 2.0,
+            false => cells as f32 * 2.0 + 1.0,
+        };
+        (
+            size(self.width, self.wrap.horizontal()),
+            size(self.height, self.wrap.vertical()),
+        )
+    }
+
+    pub fn solution(&self) -> Option<&Solution> {
+        self.solution.as_ref()
+    }
+
+    pub fn statistics(&self) -> Statistics {
+        // The solution goes through the full grid, walls and openings included.
+        let cells: Vec<Position> = self
+            .solution
+            .iter()
+            .flat_map(|solution| solution.path.iter())
+            .filter(|Position(x, y)| x % 2 == 1 && y % 2 == 1)
+            .map(|Position(x, y)| Position(x / 2, y / 2))
+            .collect();
+
+        Statistics::measure(self, &cells)
+    }
+
+    /// Finds the way from the entry to the closest exit.
+    pub fn solve(mut self, solver: Solver) -> Self {
+        self.solution = self.solve_doors(solver);
+
+        self
+    }
+}
+
+/// How to build a maze, the default being the one of the original painting.
+#[derive(Clone, Debug)]
+pub struct MazeOptions {
+    pub shape: Shape,
+    /// Restricts a square maze to the shape of a picture, a path or a text.
+    pub mask: Option<Mask>,
+    pub generator: Generator,
+    pub solver: Solver,
+    /// Where the entry and the exits of square mazes are.
+    pub placement: Placement,
+    /// Lets the passages of square mazes go under the corridors, over bridges.
+    pub weave: bool,
+    /// Which sides of square mazes lead to the opposite ones.
+    pub wrap: Wrap,
+    /// Number of levels of square mazes, stairs leading from one to the next.
+    pub levels: usize,
+    /// How the levels of a maze of several levels are drawn.
+    pub projection: Projection,
+    /// Number of cells across and down of square mazes, instead of as many as fit the canvas,
+    /// the cells being stretched over it.
+    pub cells: Option<(usize, usize)>,
+    /// How the walls of square mazes are drawn.
+    pub style: Style,
+    /// Ratio of dead ends to remove, creating loops, from 0.0 (a perfect maze) to 1.0.
+    pub braid: f32,
+    pub seed: u64,
+}
+
+impl Default for MazeOptions {
+    fn default() -> Self {
+        MazeOptions {
+            shape: Shape::Square,
+            mask: None,
+            generator: Generator::DepthFirst,
+            solver: Solver::DepthFirst,
+            placement: Placement::default(),
+            weave: false,
+            wrap: Wrap::None,
+            levels: 1,
+            projection: Projection::SideBySide,
+            cells: None,
+            style: Style::default(),
+            braid: 0.0,
+            // Using a set seed to have a reproducable maze.
+            seed: 42,
+        }
+    }
+}
+
+impl MazeOptions {
+    /// The solver actually used: with loops, a depth-first search would not find the shortest
+    /// path, so braided mazes are solved breadth-first instead.
+    pub fn effective_solver(&self) -> Solver {
+        if self.braid > 0.0 && self.solver == Solver::DepthFirst {
+            Solver::BreadthFirst
+        } else {
+            self.solver
+        }
+    }
+}
+
+pub fn draw(canvas: &mut Canvas) {
+    draw_with_options(canvas, &MazeOptions::default()).expect("The default maze is square.");
+}
+
+/// Draws a maze of any shape, returning it when it is square, with a single level, so it can be
+/// saved.
+pub fn draw_with_options(
+    canvas: &mut Canvas,
+    options: &MazeOptions,
+) -> Result<Option<Maze>, String> {
+    if options.levels > 1 {
+        let maze = LayeredMaze::fit(canvas, options)?;
+        println!("{}", maze);
+        print_solution(&maze.solution, options.effective_solver());
+        println!("{}", maze.statistics());
+        canvas.clear(Palette::BEIGE);
+        maze.draw(canvas, options.projection, &options.style);
+
+        return Ok(None);
+    }
+    if let Some(layout) = Layout::fit(
+        options.shape,
+        canvas.width() - MAZE_BORDER * 2.0,
+        canvas.height() - MAZE_BORDER * 2.0,
+    ) {
+        let maze = ShapedMaze::new(layout, options)?;
+        println!("{}", maze);
+        print_solution(&maze.solution, options.effective_solver());
+        println!("{}", maze.statistics());
+        canvas.clear(Palette::BEIGE);
+        maze.draw(canvas);
+
+        return Ok(None);
+    }
+
+    let maze = Maze::fit(canvas, options)?;
+    println!("{}", maze);
+    print_solution(&maze.solution, options.effective_solver());
+    println!("{}", maze.statistics());
+    draw_square(canvas, &maze, &options.style);
+
+    Ok(Some(maze))
+}
+
+/// Measures the maze drawn on a canvas with the given options.
+fn measure(canvas: &Canvas, options: &MazeOptions) -> Result<Statistics, String> {
+    if options.levels > 1 {
+        return Ok(LayeredMaze::fit(canvas, options)?.statistics());
+    }
+    match Layout::fit(
+        options.shape,
+        canvas.width() - MAZE_BORDER * 2.0,
+        canvas.height() - MAZE_BORDER * 2.0,
+    ) {
+        Some(layout) => Ok(ShapedMaze::new(layout, options)?.statistics()),
+        None => Ok(Maze::fit(canvas, options)?.statistics()),
+    }
+}
+
+/// Tries the seeds following the one of the options, until the maze drawn on the canvas is
+/// within `DIFFICULTY_TOLERANCE` of the target difficulty, from 0 to 100. Returns the seed of
+/// the closest maze after `tries` seeds.
+pub fn find_seed(
+    canvas: &Canvas,
+    options: &MazeOptions,
+    difficulty: f32,
+    tries: u64,
+) -> Result<u64, String> {
+    search_seed(options, difficulty, tries, |options| {
+        measure(canvas, options)
+    })
+}
+
+/// Searches for the seed of the maze closest to a difficulty, mazes being built and measured
+/// by `measure`.
+fn search_seed(
+    options: &MazeOptions,
+    difficulty: f32,
+    tries: u64,
+    measure: impl Fn(&MazeOptions) -> Result<Statistics, String>,
+) -> Result<u64, String> {
+    let (mut closest, mut closest_seed) = (f32::INFINITY, options.seed);
+    for seed in (0..tries.max(1)).map(|n| options.seed.wrapping_add(n)) {
+        let statistics = measure(&MazeOptions {
+            seed,
+            ..options.clone()
+        })?;
+        let distance = (statistics.difficulty() - difficulty).abs();
+        if distance < closest {
+            (closest, closest_seed) = (distance, seed);
+        }
+        if distance <= DIFFICULTY_TOLERANCE {
+            break;
+        }
+    }
+
+    println!(
+        "Seed {} is {:.1} away from a difficulty of {:.1}",
+        closest_seed, closest, difficulty
+    );
+    Ok(closest_seed)
+}
+
+/// Draws a square maze, whether it was just generated or loaded from a file.
+pub fn draw_square(canvas: &mut Canvas, maze: &Maze, style: &Style) {
+    canvas.clear(Palette::BEIGE);
+    maze.draw_with_style(canvas, style);
+}
+
+fn print_solution<N>(solution: &Option<Solution<N>>, solver: Solver) {
+    match solution {
+        Some(solution) => println!("Solved with {}: {}", solver, solution),
+        None => println!("No solution found with {}", solver),
+    }
+}
+
+impl Display for Maze {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Maze of dimension {}x{}", self.width, self.height)?;
+        // Larger mazes don't fit in a terminal, `to_ascii` still exports them.
+        if self.width > 20 || self.height > 20 {
+            return Ok(());
+        };
+
+        write!(f, "\n{}", self.to_ascii().trim_end_matches('\n'))
+    }
+}
+
+/// What a square maze shows on top of its walls.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Overlay {
+    /// The path from the entry to the exit, in red.
+    Solution,
+    /// Where to start and where to get out, for a puzzle to solve on paper.
+    Markers,
+}
+
+/// The number of cells across and down of the square maze drawn on a canvas.
+fn fit_size(canvas: &Canvas, options: &MazeOptions) -> (usize, usize) {
+    let (border_x, border_y) = border(options.wrap);
+    options.cells.unwrap_or((
+        ((canvas.width() - border_x * 2.0) / MAZE_TO_PIXEL) as usize,
+        ((canvas.height() - border_y * 2.0) / MAZE_TO_PIXEL) as usize,
+    ))
+}
+
+/// The cells of a square maze inside of the mask of the options, if there is one.
+fn inside_cells(
+    width: usize,
+    height: usize,
+    options: &MazeOptions,
+) -> Result<Option<Vec<bool>>, String> {
+    match &options.mask {
+        Some(mask) => Ok(Some(mask.cells(width, height)?)),
+        None => Ok(None),
+    }
+}
+
+/// Space around a maze, none along the wrapped sides.
+fn border(wrap: Wrap) -> (f32, f32) {
+    let border = |wraps: bool| if wraps { 0.0 } else { MAZE_BORDER };
+    (border(wrap.horizontal()), border(wrap.vertical()))
+}
+
+impl Drawable for Maze {
+    fn draw(&self, canvas: &mut Canvas) {
+        self.draw_with_style(canvas, &Style::default());
+    }
+}
+
+impl Maze {
+    fn draw_with_style(&self, canvas: &mut Canvas, style: &Style) {
+        let (border_x, border_y) = border(self.wrap);
+        let bounds = Rect::new(
+            border_x,
+            border_y,
+            canvas.width() - border_x,
+            canvas.height() - border_y,
+        );
+        self.draw_in(canvas, bounds, Overlay::Solution, style);
+    }
+
+    /// Draws the maze stretched over `bounds`.
+    pub(super) fn draw_in(
+        &self,
+        canvas: &mut Canvas,
+        bounds: Rect,
+        overlay: Overlay,
+        style: &Style,
+    ) {
+        canvas.save();
+        self.transform(canvas, bounds);
+        match style.walls {
+            Walls::Rounded => self.draw_floor(canvas, style),
+            Walls::Lines | Walls::Blocks | Walls::Sketch => self.draw_walls(canvas, style),
+        }
+        self.draw_wrapped_passages(canvas);
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(STROKE_WIDTH);
+        match overlay {
+            Overlay::Solution => self.draw_solution(canvas, &mut paint),
+            Overlay::Markers => self.draw_markers(canvas),
+        }
+
+        canvas.restore();
+    }
+
+    /// Draws the walls as runs along the rows and the columns, the passages going under the
+    /// bridges stopping short of them.
+    fn draw_walls(&self, canvas: &mut Canvas, style: &Style) {
+        let overhang = style.wall_overhang();
+        // The same space is left between the walls of a bridge and the ones under it, however
+        // wide the walls are.
+        let bridge_gap = BRIDGE_GAP + (style.wall_width() - STROKE_WIDTH) / 2.0;
+
+        let width = (self.width * 2 + 1) as usize;
+        let height = (self.height * 2 + 1) as usize;
+
+        // Bridges over the crossings, the walls of the passages going under them stopping short.
+        let mut bridges: Vec<Wall> = Vec::new();
+        let mut gaps: HashSet<(Position, Axis)> = HashSet::new();
+        for (index, cell) in self.data.iter().enumerate() {
+            if let CellType::Crossing(over) = cell.cell_type {
+                let (x, y) = (index % width, index / width);
+                let bridge = match over {
+                    Axis::Vertical => [
+                        Wall(Position(x - 1, y - 1), Position(x - 1, y + 1)),
+                        Wall(Position(x + 1, y - 1), Position(x + 1, y + 1)),
+                    ],
+                    Axis::Horizontal => [
+                        Wall(Position(x - 1, y - 1), Position(x + 1, y - 1)),
+                        Wall(Position(x - 1, y + 1), Position(x + 1, y + 1)),
+                    ],
+                };
+                for Wall(a, b) in bridge.iter() {
+                    gaps.extend([(*a, over.across()), (*b, over.across())]);
+                }
+                bridges.extend(bridge);
+            }
+        }
+
+        let paint = style.wall_paint();
+        let mut path = Path::new();
+        let mut segments = 0;
+        let mut add_wall = |wall: Wall, path: &mut Path| {
+            let segment = match wall.as_segment(overhang) {
+                Some(segment) => segment,
+                None => return,
+            };
+            let Wall(a, b) = wall;
+            let axis = Axis::between(a, b);
+            let (dx, dy) = match axis {
+                Axis::Horizontal => (bridge_gap, 0.0),
+                Axis::Vertical => (0.0, bridge_gap),
+            };
+            let start = match gaps.contains(&(a, axis)) {
+                true => Point::new(a.0 as f32 + dx, a.1 as f32 + dy),
+                false => segment.a(),
+            };
+            let end = match gaps.contains(&(b, axis)) {
+                true => Point::new(b.0 as f32 - dx, b.1 as f32 - dy),
+                false => segment.b(),
+            };
+            style.add_wall(path, (a, b), (start, end));
+            // Drawing huge mazes a piece at a time, instead of keeping all of their walls.
+            segments += 1;
+            if segments % WALLS_PER_PATH == 0 {
+                canvas.draw_path(path, &paint);
+                path.reset();
+            }
+        };
+
+        // The runs of walls of each row, then of each column.
+        let lines = (0..height)
+            .map(|y| (0..width).map(move |x| (x, y)).collect::<Vec<_>>())
+            .chain((0..width).map(|x| (0..height).map(move |y| (x, y)).collect::<Vec<_>>()));
+        for line in lines {
+            let mut run: Option<(Position, Position)> = None;
+            for (x, y) in line {
+                match self.get_any_cell(x, y).unwrap().cell_type {
+                    CellType::Wall => {
+                        run = match run {
+                            None => Some((Position(x, y), Position(x, y))),
+                            Some((start, _)) => Some((start, Position(x, y))),
+                        }
+                    }
+                    CellType::Floor | CellType::Outside | CellType::Crossing(_) => {
+                        if let Some((a, b)) = run.take() {
+                            add_wall(Wall(a, b), &mut path);
+                        }
+                    }
+                }
+            }
+            if let Some((a, b)) = run {
+                add_wall(Wall(a, b), &mut path);
+            }
+        }
+        for wall in bridges {
+            add_wall(wall, &mut path);
+        }
+        canvas.draw_path(&path, &paint);
+    }
+
+    /// Moves and scales the canvas so the cells of the full grid are drawn at their positions,
+    /// stretched over `bounds`.
+    fn transform(&self, canvas: &mut Canvas, bounds: Rect) {
+        canvas.translate((bounds.left, bounds.top));
+
+        let (columns, rows) = self.grid_size();
+        let scale_x = bounds.width() / columns;
+        let scale_y = bounds.height() / rows;
+
+        // The walls of the wrapped sides are right on the bounds, so copies of the maze join.
+        let (border_x, border_y) = (
+            if self.wrap.horizontal() { 0.0 } else { 0.5 },
+            if self.wrap.vertical() { 0.0 } else { 0.5 },
+        );
+        canvas.translate((border_x * scale_x, border_y * scale_y));
+        canvas.scale((scale_x, scale_y));
+    }
+
+    fn draw_solution(&self, canvas: &mut Canvas, paint: &mut Paint) {
+        let solution_path = match &self.solution {
+            Some(solution) => &solution.path[..],
+            None => &[],
+        };
+        if let (Some(start), Some(end)) = (solution_path.first(), solution_path.last()) {
+            // Coming from outside of the maze, and leaving it, through the doors on the sides.
+            let beyond = |position: &Position| {
+                let (dx, dy) = self.outward(*position).unwrap_or((0.0, 0.0));
+                (position.0 as f32 + dx * 2.0, position.1 as f32 + dy * 2.0)
+            };
+
+            let mut path = Path::new();
+            path.move_to(beyond(start));
+
+            let mut previous = start;
+            for position in solution_path {
+                let (x, y) = (position.0 as f32, position.1 as f32);
+                if let Some((dx, dy)) = self.wrapped_step(*previous, *position) {
+                    // Leaving through a side, and coming back through the opposite one.
+                    path.line_to((previous.0 as f32 + dx, previous.1 as f32 + dy));
+                    path.move_to((x - dx, y - dy));
+                }
+                path.line_to((x, y));
+                previous = position;
+            }
+            path.line_to(beyond(end));
+
+            paint.set_color(Palette::RED);
+            canvas.draw_path(&path, paint);
+        }
+    }
+
+    /// A disc at the entry, and arrows out of the exits, or squares for the exits inside.
+    fn draw_markers(&self, canvas: &mut Canvas) {
+        let doors = match &self.doors {
+            Some(doors) => doors,
+            None => return,
+        };
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_color(Palette::BLACK);
+
+        let Position(x, y) = doors.entry;
+        let (dx, dy) = self.outward(doors.entry).unwrap_or((0.0, 0.0));
+        canvas.draw_circle((x as f32 + dx, y as f32 + dy), 0.6, &paint);
+
+        for exit in doors.exits.iter() {
+            let (x, y) = (exit.0 as f32, exit.1 as f32);
+            match self.outward(*exit) {
+                Some((dx, dy)) => {
+                    let (x, y) = (x + dx * 0.5, y + dy * 0.5);
+                    let mut arrow = Path::new();
+                    arrow.move_to((x + dy * 0.7, y - dx * 0.7));
+                    arrow.line_to((x + dx * 1.2, y + dy * 1.2));
+                    arrow.line_to((x - dy * 0.7, y + dx * 0.7));
+                    arrow.close();
+                    canvas.draw_path(&arrow, &paint);
+                }
+                None => {
+                    canvas.draw_rect(Rect::from_xywh(x - 0.5, y - 0.5, 1.0, 1.0), &paint);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use super::weave::Axis;
+use super::{Cell, CellType};
+
+/// The cells of the full grid of a square maze, packed in half a byte each so mazes of tens of
+/// millions of cells fit in memory: three bits for the type of the cell and one for whether it
+/// was visited.
+#[derive(Clone)]
+pub(super) struct PackedCells {
+    bytes: Vec<u8>,
+    len: usize,
+    /// Every change made to the cells while recording, to replay the generation of a maze.
+    changes: Option<Vec<(usize, Cell)>>,
+}
+
+const VISITED: u8 = 0b1000;
+
+impl Cell {
+    fn to_bits(self) -> u8 {
+        let cell_type = match self.cell_type {
+            CellType::Wall => 0,
+            CellType::Floor => 1,
+            CellType::Outside => 2,
+            CellType::Crossing(Axis::Horizontal) => 3,
+            CellType::Crossing(Axis::Vertical) => 4,
+        };
+        match self.visited {
+            true => cell_type | VISITED,
+            false => cell_type,
+        }
+    }
+
+    fn from_bits(bits: u8) -> Cell {
+        let cell_type = match bits & !VISITED {
+            0 => CellType::Wall,
+            1 => CellType::Floor,
+            2 => CellType::Outside,
+            3 => CellType::Crossing(Axis::Horizontal),
+            _ => CellType::Crossing(Axis::Vertical),
+        };
+        Cell {
+            visited: bits & VISITED != 0,
+            cell_type,
+        }
+    }
+}
+
+impl PackedCells {
+    /// `len` walls, none of them visited.
+    pub(super) fn new(len: usize) -> Self {
+        PackedCells {
+            bytes: vec![0; len.div_ceil(2)],
+            len,
+            changes: None,
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(super) fn get(&self, index: usize) -> Option<Cell> {
+        if index >= self.len {
+            return None;
+        }
+
+        let byte = self.bytes[index / 2];
+        let bits = match index % 2 {
+            0 => byte & 0x0f,
+            _ => byte >> 4,
+        };
+        Some(Cell::from_bits(bits))
+    }
+
+    pub(super) fn set(&mut self, index: usize, cell: Cell) {
+        if let Some(changes) = &mut self.changes {
+            changes.push((index, cell));
+        }
+        let byte = &mut self.bytes[index / 2];
+        *byte = match index % 2 {
+            0 => (*byte & 0xf0) | cell.to_bits(),
+            _ => (*byte & 0x0f) | (cell.to_bits() << 4),
+        };
+    }
+
+    /// Changes the type of a cell, keeping whether it was visited.
+    pub(super) fn set_type(&mut self, index: usize, cell_type: CellType) {
+        if let Some(cell) = self.get(index) {
+            self.set(index, Cell { cell_type, ..cell });
+        }
+    }
+
+    /// Starts keeping every change made to the cells.
+    pub(super) fn record(&mut self) {
+        self.changes = Some(Vec::new());
+    }
+
+    /// The changes made since `record`, which stops recording.
+    pub(super) fn take_changes(&mut self) -> Vec<(usize, Cell)> {
+        self.changes.take().unwrap_or_default()
+    }
+
+    pub(super) fn iter(&self) -> impl Iterator<Item = Cell> + '_ {
+        (0..self.len).filter_map(|index| self.get(index))
+    }
+}
+
+/// One bit per item, for the nodes a solver has seen on huge mazes.
+pub(super) struct Bits(Vec<u64>);
+
+impl Bits {
+    pub(super) fn new(len: usize) -> Self {
+        Bits(vec![0; len.div_ceil(64)])
+    }
+
+    pub(super) fn get(&self, index: usize) -> bool {
+        self.0[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub(super) fn set(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::packed::*;
+
+    #[test]
+    fn packs_two_cells_in_a_byte() {
+        let cell_types = [
+            CellType::Wall,
+            CellType::Floor,
+            CellType::Outside,
+            CellType::Crossing(Axis::Horizontal),
+            CellType::Crossing(Axis::Vertical),
+        ];
+        let cells: Vec<Cell> = cell_types
+            .iter()
+            .flat_map(|cell_type| {
+                [false, true].map(|visited| Cell {
+                    visited,
+                    cell_type: *cell_type,
+                })
+            })
+            .collect();
+
+        let mut packed = PackedCells::new(cells.len());
+        for (index, cell) in cells.iter().enumerate() {
+            packed.set(index, *cell);
+        }
+        assert_eq!(packed.bytes.len(), 5);
+        for (index, cell) in cells.iter().enumerate() {
+            let unpacked = packed.get(index).unwrap();
+            assert!(unpacked.cell_type == cell.cell_type && unpacked.visited == cell.visited);
+        }
+        assert!(packed.get(cells.len()).is_none());
+
+        packed.set_type(3, CellType::Outside);
+        let cell = packed.get(3).unwrap();
+        assert!(cell.cell_type == CellType::Outside && cell.visited);
+        assert!(packed.get(2).unwrap().cell_type == CellType::Floor);
+    }
+}
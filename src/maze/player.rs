@@ -0,0 +1,245 @@
+use super::solver::Graph;
+use super::{CellType, Maze, Position};
+
+/// Marks of the player, of the solution, of the entry and of the exits in the terminal.
+const PLAYER: char = '@';
+const SOLUTION: char = '•';
+const ENTRY: char = 'S';
+const EXIT: char = 'E';
+
+/// A step of the player through a square maze.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Move {
+    fn direction(&self) -> (isize, isize) {
+        match self {
+            Move::Up => (0, -1),
+            Move::Down => (0, 1),
+            Move::Left => (-1, 0),
+            Move::Right => (1, 0),
+        }
+    }
+}
+
+/// Someone walking a square maze from its entry to one of its exits, one cell at a time.
+pub struct Player {
+    maze: Maze,
+    /// Where the player is on the full grid of the maze, walls included.
+    position: Position,
+    moves: usize,
+    shows_solution: bool,
+}
+
+impl Player {
+    pub fn new(maze: Maze) -> Result<Self, String> {
+        let entry = match &maze.doors {
+            Some(doors) => doors.entry,
+            None => return Err("ERROR: the maze has no entry to start from".to_string()),
+        };
+
+        Ok(Player {
+            maze,
+            position: entry,
+            moves: 0,
+            shows_solution: false,
+        })
+    }
+
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    /// Whether the player reached one of the exits.
+    pub fn is_out(&self) -> bool {
+        self.maze
+            .doors
+            .as_ref()
+            .map(|doors| doors.exits.contains(&self.position))
+            .unwrap_or(false)
+    }
+
+    pub fn toggle_solution(&mut self) {
+        self.shows_solution = !self.shows_solution;
+    }
+
+    /// Walks to the next cell in the direction of the move, if no wall is in the way. Returns
+    /// whether the player moved.
+    pub fn step(&mut self, step: Move) -> bool {
+        let mut position = self.position;
+        loop {
+            let next = self
+                .maze
+                .edges(position)
+                .into_iter()
+                .find(|next| self.direction(position, *next) == step.direction());
+            match next {
+                Some(next) => position = next,
+                None => break,
+            }
+            // Going through the openings between the cells, or under the bridges, up to a cell
+            // or a door.
+            if self.is_stop(position) {
+                break;
+            }
+        }
+
+        let moved = position != self.position && self.is_stop(position);
+        if moved {
+            self.position = position;
+            self.moves += 1;
+        }
+        moved
+    }
+
+    /// The direction of a step between two positions of the full grid, going across the
+    /// wrapped sides.
+    fn direction(&self, from: Position, to: Position) -> (isize, isize) {
+        match self.maze.wrapped_step(from, to) {
+            Some((dx, dy)) => (dx as isize, dy as isize),
+            None => (
+                (to.0 as isize - from.0 as isize).signum(),
+                (to.1 as isize - from.1 as isize).signum(),
+            ),
+        }
+    }
+
+    /// Whether a position is one where the player stops: a cell, or a door.
+    fn is_stop(&self, position: Position) -> bool {
+        let Position(x, y) = position;
+        let is_door = self
+            .maze
+            .doors
+            .as_ref()
+            .map(|doors| doors.entry == position || doors.exits.contains(&position))
+            .unwrap_or(false);
+        (x % 2 == 1 && y % 2 == 1) || is_door
+    }
+
+    /// The maze drawn with box-drawing characters, a character per cell of the full grid with
+    /// another one between the columns so the cells look square.
+    pub fn render(&self) -> String {
+        let (columns, rows) = (self.maze.width * 2 + 1, self.maze.height * 2 + 1);
+        let is_wall = |x: Option<usize>, y: Option<usize>| match x
+            .zip(y)
+            .and_then(|(x, y)| self.maze.get_any_cell(x, y))
+        {
+            Some(cell) => cell.cell_type == CellType::Wall,
+            None => false,
+        };
+        let solution: &[Position] = match (&self.maze.solution, self.shows_solution) {
+            (Some(solution), true) => &solution.path,
+            _ => &[],
+        };
+        let doors = self.maze.doors.as_ref();
+
+        let mut text = String::new();
+        for y in 0..rows {
+            for x in 0..columns {
+                let position = Position(x, y);
+                let c = if position == self.position {
+                    PLAYER
+                } else if doors.map(|doors| doors.entry) == Some(position) {
+                    ENTRY
+                } else if doors
+                    .map(|doors| doors.exits.contains(&position))
+                    .unwrap_or(false)
+                {
+                    EXIT
+                } else if is_wall(Some(x), Some(y)) {
+                    wall_char(
+                        is_wall(Some(x), y.checked_sub(1)),
+                        is_wall(Some(x), Some(y + 1)),
+                        is_wall(x.checked_sub(1), Some(y)),
+                        is_wall(Some(x + 1), Some(y)),
+                    )
+                } else if solution.contains(&position) {
+                    SOLUTION
+                } else {
+                    ' '
+                };
+                text.push(c);
+
+                if x + 1 < columns {
+                    let joins = is_wall(Some(x), Some(y)) && is_wall(Some(x + 1), Some(y));
+                    text.push(if joins { '─' } else { ' ' });
+                }
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+/// The box-drawing character of a wall, joining the walls around it.
+fn wall_char(up: bool, down: bool, left: bool, right: bool) -> char {
+    match (up, down, left, right) {
+        (false, false, false, false) => '·',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╷',
+        (false, false, true, false) => '╴',
+        (false, false, false, true) => '╶',
+        (true, true, false, false) => '│',
+        (false, false, true, true) => '─',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::player::*;
+    use crate::maze::{Generator, MazeOptions, Solver};
+
+    #[test]
+    fn walks_out_along_the_solution() {
+        let options = MazeOptions {
+            generator: Generator::Wilson,
+            solver: Solver::BreadthFirst,
+            ..MazeOptions::default()
+        };
+        let maze = Maze::new(8, 5, &options).unwrap();
+        let path = maze.solution().unwrap().path.clone();
+        let mut player = Player::new(maze).unwrap();
+
+        // Walls stop the player.
+        assert!(!player.step(Move::Left));
+        assert_eq!(player.moves(), 0);
+
+        let stops: Vec<Position> = path
+            .into_iter()
+            .filter(|position| player.is_stop(*position))
+            .collect();
+        for position in stops.iter().skip(1) {
+            let step = match player.direction(player.position, *position) {
+                (0, -1) => Move::Up,
+                (0, 1) => Move::Down,
+                (-1, 0) => Move::Left,
+                _ => Move::Right,
+            };
+            assert!(player.step(step));
+            assert_eq!(player.position, *position);
+        }
+        assert!(player.is_out());
+        assert_eq!(player.moves(), stops.len() - 1);
+
+        player.toggle_solution();
+        let text = player.render();
+        assert_eq!(text.lines().count(), 11);
+        assert!(text.lines().all(|line| line.chars().count() == 8 * 4 + 1));
+        assert!(text.contains(PLAYER) && text.contains(SOLUTION) && text.contains('─'));
+    }
+}
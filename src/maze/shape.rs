@@ -0,0 +1,740 @@
+use std::f32::consts::PI;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use skia_safe::{Canvas, Paint, PaintStyle, Path, Point, Rect};
+
+use super::grid::Grid;
+use super::solver::Graph;
+use super::{
+    MazeOptions, Placement, Solution, Statistics, Style, Wrap, MAZE_BORDER, MAZE_TO_PIXEL,
+    STROKE_WIDTH,
+};
+use crate::utils::{Bounded, Drawable, Palette};
+
+const SQRT_3: f32 = 1.732_050_8;
+
+/// The shape of the cells of a maze. Besides the square (gamma) mazes, the names of Walter D.
+/// Pullen's classification are sigma for hexagonal, delta for triangular and theta for circular.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shape {
+    Square,
+    Hexagonal,
+    Triangular,
+    Circular,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 4] = [
+        Shape::Square,
+        Shape::Hexagonal,
+        Shape::Triangular,
+        Shape::Circular,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Square => "square",
+            Shape::Hexagonal => "hexagonal",
+            Shape::Triangular => "triangular",
+            Shape::Circular => "circular",
+        }
+    }
+}
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Shape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Shape::ALL
+            .iter()
+            .find(|shape| shape.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Shape::ALL.iter().map(|s| s.name()).collect();
+                format!(
+                    "ERROR: unknown shape `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// How the cells of a maze that is not square are laid out. Cells are numbered row by row, or
+/// ring by ring, and sized so the distance between two hexagon corners, the side of a triangle
+/// or the width of a ring is 1.
+#[derive(Clone, Debug)]
+pub(super) enum Layout {
+    /// Flat topped hexagons in columns, the odd columns being shifted down by half a cell.
+    Hexagonal { columns: usize, rows: usize },
+    /// Triangles in rows, alternately pointing up and down.
+    Triangular { columns: usize, rows: usize },
+    /// Rings around a central cell, with the number of cells of each ring. A ring is subdivided
+    /// when its cells would get too wide, each cell of the inner ring leading to the same number
+    /// of cells of the outer ring.
+    Circular { rings: Vec<usize> },
+}
+
+impl Layout {
+    /// Fits as many cells of the shape as the maze of the original painting would in a size in
+    /// pixels, returning None for square mazes.
+    pub(super) fn fit(shape: Shape, width: f32, height: f32) -> Option<Layout> {
+        match shape {
+            Shape::Square => None,
+            Shape::Hexagonal => {
+                let size = MAZE_TO_PIXEL * 0.6;
+                Some(Layout::Hexagonal {
+                    columns: (((width / size - 0.5) / 1.5) as usize).max(1),
+                    rows: ((height / size / SQRT_3 - 0.5) as usize).max(1),
+                })
+            }
+            Shape::Triangular => {
+                let size = MAZE_TO_PIXEL * 1.5;
+                Some(Layout::Triangular {
+                    columns: ((width * 2.0 / size - 1.0) as usize).max(1),
+                    rows: ((height * 2.0 / size / SQRT_3) as usize).max(1),
+                })
+            }
+            Shape::Circular => {
+                let radius = width.min(height) / 2.0 / MAZE_TO_PIXEL;
+                Some(Layout::circular((radius as usize).max(1)))
+            }
+        }
+    }
+
+    /// A circular layout of `count` rings, the center cell included.
+    pub(super) fn circular(count: usize) -> Layout {
+        let mut rings = vec![1];
+        for ring in 1..count {
+            let previous = rings[ring - 1];
+            let cell_width = 2.0 * PI * ring as f32 / previous as f32;
+            rings.push(previous * (cell_width.round() as usize).max(1));
+        }
+
+        Layout::Circular { rings }
+    }
+
+    fn cell_count(&self) -> usize {
+        match self {
+            Layout::Hexagonal { columns, rows } | Layout::Triangular { columns, rows } => {
+                columns * rows
+            }
+            Layout::Circular { rings } => rings.iter().sum(),
+        }
+    }
+
+    /// Size of the whole maze, in cell units.
+    fn size(&self) -> (f32, f32) {
+        match self {
+            Layout::Hexagonal { columns, rows } => {
+                let shifted = if *columns > 1 { 0.5 } else { 0.0 };
+                (
+                    1.5 * *columns as f32 + 0.5,
+                    SQRT_3 * (*rows as f32 + shifted),
+                )
+            }
+            Layout::Triangular { columns, rows } => {
+                ((*columns as f32 + 1.0) / 2.0, *rows as f32 * SQRT_3 / 2.0)
+            }
+            Layout::Circular { rings } => (rings.len() as f32 * 2.0, rings.len() as f32 * 2.0),
+        }
+    }
+
+    /// Distance between the centers of two neighbours, to size the walls.
+    fn spacing(&self) -> f32 {
+        match self {
+            Layout::Hexagonal { .. } => SQRT_3,
+            Layout::Triangular { .. } => 1.0 / SQRT_3,
+            Layout::Circular { .. } => 1.0,
+        }
+    }
+
+    /// Ring and position in the ring of a cell of a circular layout.
+    fn polar(rings: &[usize], cell: usize) -> (usize, usize) {
+        let mut first = 0;
+        for (ring, count) in rings.iter().enumerate() {
+            if cell < first + count {
+                return (ring, cell - first);
+            }
+            first += count;
+        }
+
+        unreachable!("cell {} is outside of the maze", cell)
+    }
+
+    fn polar_index(rings: &[usize], ring: usize, position: usize) -> usize {
+        rings[..ring].iter().sum::<usize>() + position % rings[ring]
+    }
+
+    fn neighbours(&self, cell: usize) -> Vec<usize> {
+        match self {
+            Layout::Hexagonal { .. } | Layout::Triangular { .. } => self
+                .sides(cell)
+                .into_iter()
+                .filter_map(|(neighbour, _, _)| neighbour)
+                .collect(),
+            Layout::Circular { rings } => {
+                let (ring, position) = Layout::polar(rings, cell);
+                let count = rings[ring];
+                let mut neighbours = Vec::new();
+                if ring > 0 {
+                    let ratio = count / rings[ring - 1];
+                    neighbours.push(Layout::polar_index(rings, ring - 1, position / ratio));
+                    if count > 1 {
+                        neighbours.push(Layout::polar_index(rings, ring, position + 1));
+                    }
+                    if count > 2 {
+                        neighbours.push(Layout::polar_index(rings, ring, position + count - 1));
+                    }
+                }
+                if ring + 1 < rings.len() {
+                    let ratio = rings[ring + 1] / count;
+                    for child in position * ratio..(position + 1) * ratio {
+                        neighbours.push(Layout::polar_index(rings, ring + 1, child));
+                    }
+                }
+
+                neighbours
+            }
+        }
+    }
+
+    /// The sides of a hexagonal or triangular cell, with the neighbour behind each of them.
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Point, Point)> {
+        match self {
+            Layout::Hexagonal { columns, rows } => {
+                let (columns, rows) = (*columns as isize, *rows as isize);
+                let (column, row) = (cell as isize % columns, cell as isize / columns);
+                let center = self.center(cell);
+                let corner = |corner: usize| {
+                    let angle = corner as f32 * PI / 3.0;
+                    Point::new(center.x + angle.cos(), center.y + angle.sin())
+                };
+                let shift = column % 2;
+                // Clockwise from the south east side, as the corners.
+                let offsets = [
+                    (1, shift),
+                    (0, 1),
+                    (-1, shift),
+                    (-1, shift - 1),
+                    (0, -1),
+                    (1, shift - 1),
+                ];
+
+                offsets
+                    .iter()
+                    .enumerate()
+                    .map(|(side, (dx, dy))| {
+                        let (x, y) = (column + dx, row + dy);
+                        let neighbour = (x >= 0 && x < columns && y >= 0 && y < rows)
+                            .then(|| (y * columns + x) as usize);
+                        (neighbour, corner(side), corner((side + 1) % 6))
+                    })
+                    .collect()
+            }
+            Layout::Triangular { columns, rows } => {
+                let (column, row) = (cell % columns, cell / columns);
+                let x = (column as f32 + 1.0) / 2.0;
+                let (top, bottom) = (row as f32 * SQRT_3 / 2.0, (row as f32 + 1.0) * SQRT_3 / 2.0);
+                let west = (column > 0).then(|| cell - 1);
+                let east = (column + 1 < *columns).then(|| cell + 1);
+
+                if (column + row) % 2 == 0 {
+                    // Pointing up, the base being on the south side.
+                    let apex = Point::new(x, top);
+                    let (left, right) = (Point::new(x - 0.5, bottom), Point::new(x + 0.5, bottom));
+                    let south = (row + 1 < *rows).then(|| cell + columns);
+                    vec![
+                        (west, apex, left),
+                        (east, apex, right),
+                        (south, left, right),
+                    ]
+                } else {
+                    // Pointing down, the base being on the north side.
+                    let apex = Point::new(x, bottom);
+                    let (left, right) = (Point::new(x - 0.5, top), Point::new(x + 0.5, top));
+                    let north = (row > 0).then(|| cell - columns);
+                    vec![
+                        (west, left, apex),
+                        (east, right, apex),
+                        (north, left, right),
+                    ]
+                }
+            }
+            Layout::Circular { .. } => Vec::new(),
+        }
+    }
+
+    fn center(&self, cell: usize) -> Point {
+        match self {
+            Layout::Hexagonal { columns, .. } => {
+                let (column, row) = (cell % columns, cell / columns);
+                Point::new(
+                    1.0 + 1.5 * column as f32,
+                    SQRT_3 / 2.0 * (1.0 + 2.0 * row as f32 + (column % 2) as f32),
+                )
+            }
+            Layout::Triangular { columns, .. } => {
+                let (column, row) = (cell % columns, cell / columns);
+                // The centroid is a third of the height away from the base.
+                let third = if (column + row) % 2 == 0 { 2.0 } else { 1.0 };
+                Point::new(
+                    (column as f32 + 1.0) / 2.0,
+                    (row as f32 + third / 3.0) * SQRT_3 / 2.0,
+                )
+            }
+            Layout::Circular { rings } => {
+                let middle = rings.len() as f32;
+                let (ring, position) = Layout::polar(rings, cell);
+                if ring == 0 {
+                    return Point::new(middle, middle);
+                }
+                let angle = (position as f32 + 0.5) * 2.0 * PI / rings[ring] as f32;
+                let radius = ring as f32 + 0.5;
+                Point::new(middle + radius * angle.cos(), middle + radius * angle.sin())
+            }
+        }
+    }
+
+    /// The cells the maze is entered from and exited to, with the side of the cell open to the
+    /// outside: west to east, or from the center to the outer ring.
+    fn openings(&self, rng: &mut StdRng) -> (Opening, Opening) {
+        match self {
+            Layout::Hexagonal { columns, rows } => (
+                Opening::Side(rng.gen_range(0..*rows) * columns, 3),
+                Opening::Side(rng.gen_range(0..*rows) * columns + columns - 1, 0),
+            ),
+            Layout::Triangular { columns, rows } => (
+                Opening::Side(rng.gen_range(0..*rows) * columns, 0),
+                Opening::Side(rng.gen_range(0..*rows) * columns + columns - 1, 1),
+            ),
+            Layout::Circular { rings } => {
+                let ring = rings.len() - 1;
+                let position = rng.gen_range(0..rings[ring]);
+                (
+                    Opening::Center,
+                    Opening::Outer(Layout::polar_index(rings, ring, position)),
+                )
+            }
+        }
+    }
+
+    /// A number of steps that never exceeds the real distance between two cells.
+    fn estimate(&self, from: usize, to: usize) -> usize {
+        match self {
+            Layout::Hexagonal { columns, .. } => {
+                // Using the axial coordinates of the hexagons.
+                let axial = |cell: usize| {
+                    let (column, row) = ((cell % columns) as isize, (cell / columns) as isize);
+                    (column, row - (column - (column & 1)) / 2)
+                };
+                let ((qa, ra), (qb, rb)) = (axial(from), axial(to));
+                let (dq, dr) = (qa - qb, ra - rb);
+                ((dq.abs() + (dq + dr).abs() + dr.abs()) / 2) as usize
+            }
+            Layout::Triangular { columns, .. } => {
+                (from % columns).abs_diff(to % columns) + (from / columns).abs_diff(to / columns)
+            }
+            Layout::Circular { rings } => Layout::polar(rings, from)
+                .0
+                .abs_diff(Layout::polar(rings, to).0),
+        }
+    }
+}
+
+/// Where a path goes through the outer wall, or starts from.
+#[derive(Copy, Clone, Debug)]
+enum Opening {
+    /// A cell, and the index of its side open to the outside.
+    Side(usize, usize),
+    /// The middle of a circular maze.
+    Center,
+    /// A cell of the outer ring of a circular maze, open to the outside.
+    Outer(usize),
+}
+
+impl Opening {
+    fn cell(&self) -> usize {
+        match self {
+            Opening::Side(cell, _) | Opening::Outer(cell) => *cell,
+            Opening::Center => 0,
+        }
+    }
+
+    fn is_side(&self, cell: usize, side: usize) -> bool {
+        matches!(self, Opening::Side(c, s) if *c == cell && *s == side)
+    }
+}
+
+/// A maze of hexagonal, triangular or circular cells, carved by the same generators and walked
+/// by the same solvers as the square one, but keeping the passages between cells instead of a
+/// grid of walls.
+pub(super) struct ShapedMaze {
+    layout: Layout,
+    neighbours: Vec<Vec<usize>>,
+    links: Vec<Vec<usize>>,
+    visited: Vec<bool>,
+    entry: Opening,
+    exit: Opening,
+    pub(super) solution: Option<Solution<usize>>,
+}
+
+impl ShapedMaze {
+    pub(super) fn new(layout: Layout, options: &MazeOptions) -> Result<Self, String> {
+        if options.mask.is_some() {
+            return Err("ERROR: only square mazes can follow the shape of a mask".to_string());
+        }
+        if options.weave {
+            return Err("ERROR: only square mazes can weave their passages".to_string());
+        }
+        if options.wrap != Wrap::None {
+            return Err("ERROR: only square mazes can wrap around".to_string());
+        }
+        if options.cells.is_some() {
+            return Err("ERROR: only square mazes can be given a number of cells".to_string());
+        }
+        if options.style != Style::default() {
+            return Err("ERROR: only square mazes can be drawn in another style".to_string());
+        }
+        if options.placement != Placement::default() {
+            return Err("ERROR: only square mazes can place their entry and exits".to_string());
+        }
+        if options.generator.is_rectangular_only() {
+            return Err(format!(
+                "ERROR: the {} generator only works on square mazes",
+                options.generator
+            ));
+        }
+
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        let count = layout.cell_count();
+        let mut maze = ShapedMaze {
+            neighbours: (0..count).map(|cell| layout.neighbours(cell)).collect(),
+            links: vec![Vec::new(); count],
+            visited: vec![false; count],
+            entry: Opening::Center,
+            exit: Opening::Center,
+            solution: None,
+            layout,
+        };
+
+        options.generator.generate_on_grid(&mut maze, &mut rng);
+        super::generator::braid(&mut maze, options.braid, &mut rng);
+        (maze.entry, maze.exit) = maze.layout.openings(&mut rng);
+        maze.solution =
+            options
+                .effective_solver()
+                .solve(&maze, maze.entry.cell(), maze.exit.cell());
+
+        Ok(maze)
+    }
+
+    pub(super) fn statistics(&self) -> Statistics {
+        let path = self
+            .solution
+            .as_ref()
+            .map(|solution| solution.path.as_slice());
+        Statistics::measure(self, path.unwrap_or_default())
+    }
+
+    /// Midpoint of the side of a cell, or its center, where the solution starts or ends.
+    fn opening_point(&self, opening: Opening) -> Point {
+        match (opening, &self.layout) {
+            (Opening::Side(cell, side), _) => {
+                let (_, a, b) = self.layout.sides(cell)[side];
+                Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0)
+            }
+            (Opening::Outer(cell), Layout::Circular { rings }) => {
+                let middle = rings.len() as f32;
+                let direction = self.layout.center(cell) - Point::new(middle, middle);
+                let scale = middle / direction.length();
+                Point::new(middle + direction.x * scale, middle + direction.y * scale)
+            }
+            _ => self.layout.center(opening.cell()),
+        }
+    }
+
+    fn walls(&self) -> Path {
+        let mut path = Path::new();
+
+        match &self.layout {
+            Layout::Hexagonal { .. } | Layout::Triangular { .. } => {
+                for cell in 0..self.layout.cell_count() {
+                    for (side, (neighbour, a, b)) in self.layout.sides(cell).into_iter().enumerate()
+                    {
+                        let is_wall = match neighbour {
+                            // Shared walls are drawn once, from the cell with the lowest index.
+                            Some(neighbour) => neighbour > cell && !self.is_linked(cell, neighbour),
+                            None => {
+                                !self.entry.is_side(cell, side) && !self.exit.is_side(cell, side)
+                            }
+                        };
+                        if is_wall {
+                            path.move_to(a);
+                            path.line_to(b);
+                        }
+                    }
+                }
+            }
+            Layout::Circular { rings } => {
+                let middle = rings.len() as f32;
+                let circle = |radius: f32| {
+                    Rect::from_xywh(middle - radius, middle - radius, radius * 2.0, radius * 2.0)
+                };
+                let last_ring = rings.len() - 1;
+
+                for cell in 1..self.layout.cell_count() {
+                    let (ring, position) = Layout::polar(rings, cell);
+                    let count = rings[ring];
+                    let sweep = 360.0 / count as f32;
+                    let start = position as f32 * sweep;
+
+                    let inward = self.neighbours[cell][0];
+                    if !self.is_linked(cell, inward) {
+                        path.add_arc(circle(ring as f32), start, sweep);
+                    }
+
+                    let clockwise = Layout::polar_index(rings, ring, position + 1);
+                    if count > 1 && !self.is_linked(cell, clockwise) {
+                        let angle = (start + sweep).to_radians();
+                        let (cos, sin) = (angle.cos(), angle.sin());
+                        let (inner, outer) = (ring as f32, ring as f32 + 1.0);
+                        path.move_to((middle + inner * cos, middle + inner * sin));
+                        path.line_to((middle + outer * cos, middle + outer * sin));
+                    }
+
+                    if ring == last_ring && self.exit.cell() != cell {
+                        path.add_arc(circle(ring as f32 + 1.0), start, sweep);
+                    }
+                }
+            }
+        }
+
+        path
+    }
+}
+
+impl Grid for ShapedMaze {
+    type Cell = usize;
+
+    fn cells(&self) -> Vec<usize> {
+        (0..self.layout.cell_count()).collect()
+    }
+
+    fn cell_index(&self, cell: usize) -> usize {
+        cell
+    }
+
+    fn index_count(&self) -> usize {
+        self.layout.cell_count()
+    }
+
+    fn cell_at(&self, index: usize) -> Option<usize> {
+        (index < self.layout.cell_count()).then_some(index)
+    }
+
+    fn random_cell(&self, rng: &mut StdRng) -> usize {
+        rng.gen_range(0..self.layout.cell_count())
+    }
+
+    fn neighbours(&self, cell: usize) -> Vec<usize> {
+        self.neighbours[cell].clone()
+    }
+
+    fn link(&mut self, cell_a: usize, cell_b: usize) {
+        if !self.is_linked(cell_a, cell_b) {
+            self.links[cell_a].push(cell_b);
+            self.links[cell_b].push(cell_a);
+        }
+    }
+
+    fn is_linked(&self, cell_a: usize, cell_b: usize) -> bool {
+        self.links[cell_a].contains(&cell_b)
+    }
+
+    fn visit(&mut self, cell: usize) {
+        self.visited[cell] = true;
+    }
+
+    fn is_visited(&self, cell: usize) -> bool {
+        self.visited[cell]
+    }
+}
+
+impl Graph for ShapedMaze {
+    type Node = usize;
+
+    fn node_count(&self) -> usize {
+        self.layout.cell_count()
+    }
+
+    fn index(&self, cell: usize) -> usize {
+        cell
+    }
+
+    fn node(&self, index: usize) -> Option<usize> {
+        (index < self.layout.cell_count()).then_some(index)
+    }
+
+    fn edges(&self, cell: usize) -> Vec<usize> {
+        self.links[cell].clone()
+    }
+
+    fn nodes(&self) -> Vec<usize> {
+        self.cells()
+    }
+
+    fn estimate(&self, from: usize, to: usize) -> usize {
+        self.layout.estimate(from, to)
+    }
+}
+
+impl Display for ShapedMaze {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.layout {
+            Layout::Hexagonal { columns, rows } => {
+                write!(f, "Hexagonal maze of dimension {}x{}", columns, rows)
+            }
+            Layout::Triangular { columns, rows } => {
+                write!(f, "Triangular maze of dimension {}x{}", columns, rows)
+            }
+            Layout::Circular { rings } => write!(
+                f,
+                "Circular maze of {} rings, {} cells",
+                rings.len(),
+                self.layout.cell_count()
+            ),
+        }
+    }
+}
+
+impl Drawable for ShapedMaze {
+    fn draw(&self, canvas: &mut Canvas) {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Stroke);
+
+        let (width, height) = self.layout.size();
+        let scale = ((canvas.width() - MAZE_BORDER * 2.0) / width)
+            .min((canvas.height() - MAZE_BORDER * 2.0) / height);
+
+        canvas.save();
+        canvas.translate((
+            (canvas.width() - width * scale) / 2.0,
+            (canvas.height() - height * scale) / 2.0,
+        ));
+        canvas.scale((scale, scale));
+        paint.set_stroke_width(STROKE_WIDTH / 2.0 * self.layout.spacing());
+
+        paint.set_color(Palette::BLACK);
+        canvas.draw_path(&self.walls(), &paint);
+
+        // Draw solution.
+        if let Some(solution) = &self.solution {
+            let mut path = Path::new();
+            path.move_to(self.opening_point(self.entry));
+            for cell in &solution.path {
+                path.line_to(self.layout.center(*cell));
+            }
+            path.line_to(self.opening_point(self.exit));
+
+            paint.set_color(Palette::RED);
+            canvas.draw_path(&path, &paint);
+        }
+
+        canvas.restore();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::shape::*;
+    use crate::maze::Generator;
+
+    fn assert_perfect(maze: &ShapedMaze) {
+        let count = maze.layout.cell_count();
+        let links: usize = maze.links.iter().map(|links| links.len()).sum();
+        assert_eq!(links / 2, count - 1);
+
+        let mut seen = vec![false; count];
+        let mut stack = vec![0];
+        while let Some(cell) = stack.pop() {
+            if !seen[cell] {
+                seen[cell] = true;
+                stack.extend(maze.links[cell].iter().copied());
+            }
+        }
+        assert!(seen.into_iter().all(|seen| seen));
+    }
+
+    #[test]
+    fn generates_perfect_mazes_on_every_shape() {
+        let layouts = [
+            Layout::Hexagonal {
+                columns: 9,
+                rows: 7,
+            },
+            Layout::Triangular {
+                columns: 9,
+                rows: 7,
+            },
+            Layout::circular(6),
+        ];
+        for layout in layouts {
+            let generators = Generator::ALL.iter().filter(|g| !g.is_rectangular_only());
+            // Several seeds, as a generator coming back to its first cell only does so on some.
+            for (generator, seed) in generators.flat_map(|g| (0..4).map(move |seed| (*g, seed))) {
+                let options = MazeOptions {
+                    generator,
+                    seed,
+                    ..MazeOptions::default()
+                };
+                let maze = ShapedMaze::new(layout.clone(), &options).unwrap();
+                assert_perfect(&maze);
+
+                let solution = maze.solution.as_ref().unwrap();
+                assert_eq!(solution.path.first(), Some(&maze.entry.cell()));
+                assert_eq!(solution.path.last(), Some(&maze.exit.cell()));
+            }
+        }
+    }
+
+    #[test]
+    fn neighbours_are_mutual() {
+        let layouts = [
+            Layout::Hexagonal {
+                columns: 4,
+                rows: 3,
+            },
+            Layout::Triangular {
+                columns: 5,
+                rows: 3,
+            },
+            Layout::circular(5),
+        ];
+        for layout in layouts {
+            for cell in 0..layout.cell_count() {
+                for neighbour in layout.neighbours(cell) {
+                    assert!(layout.neighbours(neighbour).contains(&cell));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_row_based_generators() {
+        let options = MazeOptions {
+            generator: Generator::Sidewinder,
+            ..MazeOptions::default()
+        };
+        assert!(ShapedMaze::new(Layout::circular(4), &options).is_err());
+    }
+}
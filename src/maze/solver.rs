@@ -0,0 +1,444 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::packed::Bits;
+use super::weave::{beyond, Axis};
+use super::{CellType, Maze, Position};
+
+/// The algorithms available to find the way out.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Solver {
+    /// Follows the first open way, backtracking on dead ends. Only finds the shortest path in
+    /// perfect mazes, where there is a single path.
+    DepthFirst,
+    /// Explores the maze in rings of growing distance from the start.
+    BreadthFirst,
+    /// Explores the most promising cells first, using an estimate of the distance to the exit.
+    AStar,
+    /// Fills every dead end until only the corridors between the entry and the exit remain.
+    DeadEndFilling,
+}
+
+/// A path from the entry to the exit, and how much of the maze was explored to find it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Solution<N = Position> {
+    pub path: Vec<N>,
+    pub visited: usize,
+}
+
+/// What a solver does at each step of its search, for animations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Step<N> {
+    /// The node is reached for the first time.
+    Visit(N),
+    /// The node leads nowhere, the solver turning back from it.
+    Backtrack(N),
+}
+
+/// What solvers need to know about a maze to walk it: the nodes that can be reached from a node.
+pub(super) trait Graph {
+    type Node: Copy + PartialEq;
+
+    fn node_count(&self) -> usize;
+
+    /// Index of a node, from 0 to the number of nodes.
+    fn index(&self, node: Self::Node) -> usize;
+
+    /// The node of an index, if it is one.
+    fn node(&self, index: usize) -> Option<Self::Node>;
+
+    /// The nodes that can be reached from a node in a single step.
+    fn edges(&self, node: Self::Node) -> Vec<Self::Node>;
+
+    /// Every node that can be walked on.
+    fn nodes(&self) -> Vec<Self::Node>;
+
+    /// A number of steps that never exceeds the real distance between two nodes.
+    fn estimate(&self, from: Self::Node, to: Self::Node) -> usize;
+
+    /// The number of steps of an edge.
+    fn cost(&self, _from: Self::Node, _to: Self::Node) -> usize {
+        1
+    }
+}
+
+impl Solver {
+    pub const ALL: [Solver; 4] = [
+        Solver::DepthFirst,
+        Solver::BreadthFirst,
+        Solver::AStar,
+        Solver::DeadEndFilling,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Solver::DepthFirst => "depth-first",
+            Solver::BreadthFirst => "breadth-first",
+            Solver::AStar => "a-star",
+            Solver::DeadEndFilling => "dead-end-filling",
+        }
+    }
+
+    pub(super) fn solve<G: Graph>(
+        &self,
+        graph: &G,
+        start: G::Node,
+        goal: G::Node,
+    ) -> Option<Solution<G::Node>> {
+        self.trace(graph, start, goal, &mut |_| ())
+    }
+
+    /// Solves the graph, telling `trace` about each step of the search.
+    pub(super) fn trace<G: Graph>(
+        &self,
+        graph: &G,
+        start: G::Node,
+        goal: G::Node,
+        trace: &mut impl FnMut(Step<G::Node>),
+    ) -> Option<Solution<G::Node>> {
+        match self {
+            Solver::DepthFirst => depth_first(graph, start, goal, trace),
+            Solver::BreadthFirst => breadth_first(graph, start, goal, |_| true, trace),
+            Solver::AStar => a_star(graph, start, goal, trace),
+            Solver::DeadEndFilling => dead_end_filling(graph, start, goal, trace),
+        }
+    }
+}
+
+impl Display for Solver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Solver {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Solver::ALL
+            .iter()
+            .find(|solver| solver.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Solver::ALL.iter().map(|s| s.name()).collect();
+                format!(
+                    "ERROR: unknown solver `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl<N> Display for Solution<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "path of {} cells, {} cells visited",
+            self.path.len(),
+            self.visited
+        )
+    }
+}
+
+fn depth_first<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    goal: G::Node,
+    trace: &mut impl FnMut(Step<G::Node>),
+) -> Option<Solution<G::Node>> {
+    // A bit per node, as huge mazes have hundreds of millions of them.
+    let mut seen = Bits::new(graph.node_count());
+    let mut path: Vec<G::Node> = vec![start];
+    seen.set(graph.index(start));
+    trace(Step::Visit(start));
+    let mut visited = 1;
+
+    loop {
+        let current = *path.last()?;
+        if current == goal {
+            break;
+        }
+
+        let unvisited = graph
+            .edges(current)
+            .into_iter()
+            .find(|neighbour| !seen.get(graph.index(*neighbour)));
+        match unvisited {
+            Some(neighboor) => {
+                seen.set(graph.index(neighboor));
+                trace(Step::Visit(neighboor));
+                visited += 1;
+                path.push(neighboor);
+            }
+            None => {
+                path.pop();
+                trace(Step::Backtrack(current));
+            }
+        }
+    }
+
+    Some(Solution { path, visited })
+}
+
+/// Marks the nodes without a previous node in the searches, the start and the unreached ones.
+const NO_PREVIOUS: usize = usize::MAX;
+
+/// Breadth first search, limited to the nodes accepted by `walkable`. The previous nodes are kept
+/// by index, a word per node, so it takes more memory than the depth-first search on huge mazes.
+fn breadth_first<G, F>(
+    graph: &G,
+    start: G::Node,
+    goal: G::Node,
+    walkable: F,
+    trace: &mut impl FnMut(Step<G::Node>),
+) -> Option<Solution<G::Node>>
+where
+    G: Graph,
+    F: Fn(G::Node) -> bool,
+{
+    let mut previous = vec![NO_PREVIOUS; graph.node_count()];
+    let mut visited = Bits::new(graph.node_count());
+    let mut queue: VecDeque<G::Node> = VecDeque::new();
+
+    visited.set(graph.index(start));
+    trace(Step::Visit(start));
+    queue.push_back(start);
+    let mut visited_count = 1;
+
+    while let Some(current) = queue.pop_front() {
+        if current == goal {
+            return Some(Solution {
+                path: walk_back(graph, &previous, goal),
+                visited: visited_count,
+            });
+        }
+
+        for neighbour in graph.edges(current) {
+            let index = graph.index(neighbour);
+            if !visited.get(index) && walkable(neighbour) {
+                visited.set(index);
+                trace(Step::Visit(neighbour));
+                visited_count += 1;
+                previous[index] = graph.index(current);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    None
+}
+
+/// A* search, keeping by index the previous node and the distance of each node, like the
+/// breadth-first search.
+fn a_star<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    goal: G::Node,
+    trace: &mut impl FnMut(Step<G::Node>),
+) -> Option<Solution<G::Node>> {
+    let mut previous = vec![NO_PREVIOUS; graph.node_count()];
+    let mut distances: Vec<usize> = vec![usize::MAX; graph.node_count()];
+    let mut closed = Bits::new(graph.node_count());
+    // Ordered by estimated total distance, then by distance from the start, then by index.
+    let mut open: BinaryHeap<Reverse<(usize, usize, usize)>> = BinaryHeap::new();
+
+    let start_index = graph.index(start);
+    distances[start_index] = 0;
+    open.push(Reverse((graph.estimate(start, goal), 0, start_index)));
+    let mut visited_count = 0;
+
+    while let Some(Reverse((_, distance, index))) = open.pop() {
+        if closed.get(index) {
+            continue;
+        }
+        closed.set(index);
+        visited_count += 1;
+
+        let current = graph.node(index)?;
+        trace(Step::Visit(current));
+        if current == goal {
+            return Some(Solution {
+                path: walk_back(graph, &previous, goal),
+                visited: visited_count,
+            });
+        }
+
+        for neighbour in graph.edges(current) {
+            let neighbour_index = graph.index(neighbour);
+            let neighbour_distance = distance + graph.cost(current, neighbour);
+            if neighbour_distance < distances[neighbour_index] {
+                distances[neighbour_index] = neighbour_distance;
+                previous[neighbour_index] = index;
+                open.push(Reverse((
+                    neighbour_distance + graph.estimate(neighbour, goal),
+                    neighbour_distance,
+                    neighbour_index,
+                )));
+            }
+        }
+    }
+
+    None
+}
+
+/// Fills the dead ends, then walks what remains. In a perfect maze only the solution remains,
+/// otherwise the shortest of the remaining paths is picked.
+fn dead_end_filling<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    goal: G::Node,
+    trace: &mut impl FnMut(Step<G::Node>),
+) -> Option<Solution<G::Node>> {
+    let mut filled = Bits::new(graph.node_count());
+    let open_neighbours = |node: G::Node, filled: &Bits| {
+        graph
+            .edges(node)
+            .into_iter()
+            .filter(|neighbour| !filled.get(graph.index(*neighbour)))
+            .collect::<Vec<G::Node>>()
+    };
+
+    let mut dead_ends: Vec<G::Node> = graph
+        .nodes()
+        .into_iter()
+        .filter(|node| *node != start && *node != goal)
+        .filter(|node| open_neighbours(*node, &filled).len() <= 1)
+        .collect();
+    let mut filled_count = 0;
+
+    while let Some(dead_end) = dead_ends.pop() {
+        let index = graph.index(dead_end);
+        if filled.get(index) {
+            continue;
+        }
+        filled.set(index);
+        trace(Step::Backtrack(dead_end));
+        filled_count += 1;
+
+        // Filling a dead end might turn the node it led to into a new dead end.
+        for neighbour in open_neighbours(dead_end, &filled) {
+            if neighbour != start
+                && neighbour != goal
+                && open_neighbours(neighbour, &filled).len() <= 1
+            {
+                dead_ends.push(neighbour);
+            }
+        }
+    }
+
+    let solution = breadth_first(
+        graph,
+        start,
+        goal,
+        |node| !filled.get(graph.index(node)),
+        trace,
+    )?;
+
+    Some(Solution {
+        path: solution.path,
+        visited: solution.visited + filled_count,
+    })
+}
+
+/// The path to the goal, following the indexes of the previous nodes back to the start.
+fn walk_back<G: Graph>(graph: &G, previous: &[usize], goal: G::Node) -> Vec<G::Node> {
+    let mut path = vec![goal];
+    let mut index = graph.index(goal);
+    while previous[index] != NO_PREVIOUS {
+        index = previous[index];
+        path.extend(graph.node(index));
+    }
+    path.reverse();
+
+    path
+}
+
+/// The rectangular maze is walked on its full grid, walls included, each floor cell being a node.
+impl Graph for Maze {
+    type Node = Position;
+
+    fn node_count(&self) -> usize {
+        self.data.len()
+    }
+
+    fn index(&self, position: Position) -> usize {
+        position.1 * (self.width * 2 + 1) + position.0
+    }
+
+    fn node(&self, index: usize) -> Option<Position> {
+        let width = self.width * 2 + 1;
+        (index < self.data.len()).then_some(Position(index % width, index / width))
+    }
+
+    /// The floor cells of the full grid next to a position, or past the crossings it goes under.
+    fn edges(&self, position: Position) -> Vec<Position> {
+        let Position(x, y) = position;
+        let mut neighbours: Vec<Position> = Vec::with_capacity(4);
+        neighbours.push(Position(x, y + 1));
+        if y > 0 {
+            neighbours.push(Position(x, y - 1));
+        }
+        neighbours.push(Position(x + 1, y));
+        if x > 0 {
+            neighbours.push(Position(x - 1, y));
+        }
+        // Leaving through a wrapped side comes back through the opposite one.
+        if self.wrap.horizontal() {
+            if x == 0 {
+                neighbours.push(Position(self.width * 2 - 1, y));
+            }
+            if x == self.width * 2 {
+                neighbours.push(Position(1, y));
+            }
+        }
+        if self.wrap.vertical() {
+            if y == 0 {
+                neighbours.push(Position(x, self.height * 2 - 1));
+            }
+            if y == self.height * 2 {
+                neighbours.push(Position(x, 1));
+            }
+        }
+
+        // Crossings only lead along the corridor going over them.
+        let over = self.crossing_at(x, y);
+        neighbours
+            .into_iter()
+            .filter(|next| {
+                let axis = Axis::between(position, *next);
+                over.unwrap_or(axis) == axis
+            })
+            .filter_map(|next| match self.get_any_cell(next.0, next.1)?.cell_type {
+                CellType::Floor => Some(next),
+                CellType::Crossing(over) if over == Axis::between(position, next) => Some(next),
+                CellType::Crossing(_) => beyond(position, next),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn nodes(&self) -> Vec<Position> {
+        let width = self.width * 2 + 1;
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| matches!(cell.cell_type, CellType::Floor | CellType::Crossing(_)))
+            .map(|(index, _)| Position(index % width, index / width))
+            .collect()
+    }
+
+    /// The manhattan distance, across the wrapped sides when it is shorter.
+    fn estimate(&self, from: Position, to: Position) -> usize {
+        self.distance(from, to)
+    }
+
+    /// Two steps to go under a crossing.
+    fn cost(&self, from: Position, to: Position) -> usize {
+        self.estimate(from, to)
+    }
+}
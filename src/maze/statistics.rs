@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use super::grid::Grid;
+
+/// How much the length of the solution, relative to the maze, weights in the difficulty.
+const COVERAGE_WEIGHT: f32 = 0.4;
+/// How much the branches along the solution weight in the difficulty.
+const BRANCHING_WEIGHT: f32 = 0.3;
+/// How much the length of the dead ends weights in the difficulty.
+const RIVER_WEIGHT: f32 = 0.3;
+/// Solutions going through this share of the maze, or more, make the hardest mazes.
+const LONGEST_SOLUTION: f32 = 0.2;
+/// Dead ends this long on average, or longer, make the hardest mazes.
+const LONGEST_RIVER: f32 = 5.0;
+
+/// Metrics of a maze, counted in cells of the maze, and not of the full grid of the square mazes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    pub cells: usize,
+    /// Cells with a single passage.
+    pub dead_ends: usize,
+    /// Number of corridors of each length, corridors going from a dead end or a junction to the
+    /// next one, and their length being the number of steps along them.
+    pub corridors: BTreeMap<usize, usize>,
+    /// Number of cells of the solution, the entry and the exit included.
+    pub solution_length: usize,
+    /// Average number of passages leaving the solution at each of its cells.
+    pub branching_factor: f32,
+    /// Average number of steps from a dead end to the junction it leads to: high in mazes with
+    /// a few long dead ends, like the ones of the depth-first search, low in mazes with many
+    /// short ones, like Prim's.
+    pub river_factor: f32,
+}
+
+impl Statistics {
+    /// Measures a maze carved in a grid, and the cells of its solution.
+    pub(super) fn measure<G: Grid>(grid: &G, solution: &[G::Cell]) -> Self {
+        // Walking the cells by index rather than listing them, so huge mazes are measured
+        // without copies of their cells or passages.
+        let cells = || (0..grid.index_count()).filter_map(|index| grid.cell_at(index));
+        let passages_of = |cell: G::Cell| grid.passages(cell);
+        let is_corridor = |cell: G::Cell| passages_of(cell).len() == 2;
+
+        // Walks from a cell through a passage, until a cell that is not in a corridor.
+        let walk = |from: G::Cell, through: G::Cell| {
+            let (mut previous, mut current, mut steps) = (from, through, 1);
+            while is_corridor(current) && current != from {
+                let next = passages_of(current)
+                    .iter()
+                    .copied()
+                    .find(|next| *next != previous)
+                    .unwrap_or(previous);
+                (previous, current, steps) = (current, next, steps + 1);
+            }
+            (current, previous, steps)
+        };
+
+        let mut corridors: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut dead_ends = 0;
+        let mut dead_end_steps = 0;
+        for cell in cells().filter(|cell| !is_corridor(*cell)) {
+            let passages = passages_of(cell);
+            let is_dead_end = passages.len() == 1;
+            if is_dead_end {
+                dead_ends += 1;
+            }
+
+            for through in passages {
+                let (end, last, steps) = walk(cell, through);
+                if is_dead_end {
+                    dead_end_steps += steps;
+                }
+                // Each corridor is walked from both of its ends, but only counted once.
+                let from = (grid.cell_index(cell), grid.cell_index(through));
+                let to = (grid.cell_index(end), grid.cell_index(last));
+                if from <= to {
+                    *corridors.entry(steps).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let branches: usize = solution
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| {
+                let before = index.checked_sub(1).map(|before| solution[before]);
+                let after = solution.get(index + 1).copied();
+                passages_of(*cell)
+                    .iter()
+                    .filter(|passage| Some(**passage) != before && Some(**passage) != after)
+                    .count()
+            })
+            .sum();
+
+        Statistics {
+            cells: cells().count(),
+            dead_ends,
+            corridors,
+            solution_length: solution.len(),
+            branching_factor: ratio(branches, solution.len()),
+            river_factor: ratio(dead_end_steps, dead_ends),
+        }
+    }
+
+    /// Share of the maze the solution goes through.
+    pub fn solution_ratio(&self) -> f32 {
+        ratio(self.solution_length, self.cells)
+    }
+
+    pub fn mean_corridor_length(&self) -> f32 {
+        let count: usize = self.corridors.values().sum();
+        let steps: usize = self
+            .corridors
+            .iter()
+            .map(|(length, count)| length * count)
+            .sum();
+        ratio(steps, count)
+    }
+
+    /// A score from 0 to 100 for puzzles: the longer the solution, the more branches along it
+    /// and the longer the dead ends they lead to, the harder the maze.
+    pub fn difficulty(&self) -> f32 {
+        let coverage = (self.solution_ratio() / LONGEST_SOLUTION).min(1.0);
+        let branching = self.branching_factor.min(1.0);
+        let river = (self.river_factor / LONGEST_RIVER).min(1.0);
+
+        100.0 * (COVERAGE_WEIGHT * coverage + BRANCHING_WEIGHT * branching + RIVER_WEIGHT * river)
+    }
+}
+
+fn ratio(count: usize, total: usize) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f32 / total as f32
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Cells: {}, dead ends: {} ({:.1}%)",
+            self.cells,
+            self.dead_ends,
+            ratio(self.dead_ends, self.cells) * 100.0
+        )?;
+
+        // Corridor lengths, grouped by powers of two.
+        let mut groups: Vec<String> = Vec::new();
+        let mut low = 1;
+        while let Some((longest, _)) = self.corridors.range(low..).next_back() {
+            let high = low * 2 - 1;
+            let count: usize = self.corridors.range(low..=high).map(|(_, c)| c).sum();
+            if count > 0 {
+                let lengths = match low == high {
+                    true => format!("{}", low),
+                    false => format!("{}-{}", low, high.min(*longest)),
+                };
+                groups.push(format!("{}: {}", lengths, count));
+            }
+            low *= 2;
+        }
+        writeln!(
+            f,
+            "Corridors: {}, mean length {:.1} ({})",
+            self.corridors.values().sum::<usize>(),
+            self.mean_corridor_length(),
+            groups.join(", ")
+        )?;
+
+        writeln!(
+            f,
+            "Solution: {} cells ({:.1}% of the maze), {:.2} branches per cell",
+            self.solution_length,
+            self.solution_ratio() * 100.0,
+            self.branching_factor
+        )?;
+        writeln!(f, "River factor: {:.2}", self.river_factor)?;
+        write!(f, "Difficulty: {:.1}/100", self.difficulty())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::statistics::*;
+    use crate::maze::{Maze, Solver};
+
+    #[test]
+    fn measures_a_small_maze() {
+        let ascii = "\
+# # # # # # #
+        #
+# # #   #   #
+#           #
+# # # # # # #
+";
+        let maze = Maze::from_ascii(ascii).unwrap().solve(Solver::BreadthFirst);
+        let statistics = maze.statistics();
+
+        assert_eq!(statistics.cells, 6);
+        assert_eq!(statistics.dead_ends, 3);
+        assert_eq!(statistics.corridors, BTreeMap::from([(1, 1), (2, 2)]));
+        assert_eq!(statistics.solution_length, 5);
+        assert_eq!(statistics.branching_factor, 0.2);
+        assert_eq!(statistics.river_factor, 5.0 / 3.0);
+    }
+}
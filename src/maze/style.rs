@@ -0,0 +1,353 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use skia_safe::canvas::SaveLayerRec;
+use skia_safe::{BlendMode, Canvas, Paint, PaintCap, PaintJoin, PaintStyle, Path, Point, Rect};
+
+use super::weave::Axis;
+use super::{CellType, Maze, Position, STROKE_WIDTH, WALLS_PER_PATH};
+use crate::utils::Palette;
+
+/// How far sketched walls stray from where they should be, in cells of the full grid.
+const SKETCH_JITTER: f32 = 0.15;
+
+/// How the walls of a square maze are drawn.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Walls {
+    /// Thin straight lines, as in the original painting.
+    #[default]
+    Lines,
+    /// Solid blocks filling the walls of the full grid.
+    Blocks,
+    /// The corridors carved out of a solid block, rounded at their corners and dead ends.
+    Rounded,
+    /// Wobbly lines, as if drawn by hand.
+    Sketch,
+}
+
+impl Walls {
+    pub const ALL: [Walls; 4] = [Walls::Lines, Walls::Blocks, Walls::Rounded, Walls::Sketch];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Walls::Lines => "lines",
+            Walls::Blocks => "blocks",
+            Walls::Rounded => "rounded",
+            Walls::Sketch => "sketch",
+        }
+    }
+
+    /// How many times as wide as the walls the corridors are, unless told otherwise.
+    fn corridor_ratio(&self) -> f32 {
+        match self {
+            Walls::Lines | Walls::Sketch => 3.0,
+            Walls::Blocks => 1.0,
+            Walls::Rounded => 2.0,
+        }
+    }
+}
+
+impl Display for Walls {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Walls {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Walls::ALL
+            .iter()
+            .find(|walls| walls.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Walls::ALL.iter().map(|w| w.name()).collect();
+                format!(
+                    "ERROR: unknown walls `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// How a square maze is drawn, picked each time it is drawn rather than when it is built.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Style {
+    pub walls: Walls,
+    /// How many times as wide as the walls the corridors are.
+    pub corridor_ratio: f32,
+}
+
+impl Style {
+    pub fn new(walls: Walls) -> Self {
+        Style {
+            walls,
+            corridor_ratio: walls.corridor_ratio(),
+        }
+    }
+
+    /// Width of the walls, in cells of the full grid. A wall and a corridor take two cells
+    /// together, from the middle of a wall to the middle of the next one.
+    pub(super) fn wall_width(&self) -> f32 {
+        2.0 / (1.0 + self.corridor_ratio)
+    }
+
+    fn corridor_width(&self) -> f32 {
+        2.0 - self.wall_width()
+    }
+
+    /// How far the walls go past their ends, to join the walls across.
+    pub(super) fn wall_overhang(&self) -> f32 {
+        match self.walls {
+            Walls::Blocks => self.wall_width() / 2.0,
+            _ => self.wall_width() * 0.45,
+        }
+    }
+
+    /// The paint of the walls drawn by `add_wall`.
+    pub(super) fn wall_paint(&self) -> Paint {
+        let mut paint = Paint::default();
+        paint.set_color(Palette::BLACK);
+        match self.walls {
+            // Crisp blocks, without seams between the walls meeting at their corners.
+            Walls::Blocks => paint.set_style(PaintStyle::Fill),
+            Walls::Lines | Walls::Rounded => paint
+                .set_anti_alias(true)
+                .set_style(PaintStyle::Stroke)
+                .set_stroke_width(self.wall_width()),
+            Walls::Sketch => paint
+                .set_anti_alias(true)
+                .set_style(PaintStyle::Stroke)
+                .set_stroke_width(self.wall_width())
+                .set_stroke_cap(PaintCap::Round)
+                .set_stroke_join(PaintJoin::Round),
+        };
+        paint
+    }
+
+    /// Adds the wall between two positions of the full grid to a path, from `start` to `end`,
+    /// which go past the positions or stop short of them.
+    pub(super) fn add_wall(
+        &self,
+        path: &mut Path,
+        (a, b): (Position, Position),
+        (start, end): (Point, Point),
+    ) {
+        match self.walls {
+            Walls::Lines | Walls::Rounded => {
+                path.move_to(start);
+                path.line_to(end);
+            }
+            Walls::Blocks => {
+                // The ends already go past the positions, only the sides are added.
+                let half = self.wall_width() / 2.0;
+                let (across_x, across_y) = match Axis::between(a, b) {
+                    Axis::Horizontal => (0.0, half),
+                    Axis::Vertical => (half, 0.0),
+                };
+                path.add_rect(
+                    Rect::new(
+                        start.x.min(end.x) - across_x,
+                        start.y.min(end.y) - across_y,
+                        start.x.max(end.x) + across_x,
+                        start.y.max(end.y) + across_y,
+                    ),
+                    None,
+                );
+            }
+            Walls::Sketch => {
+                // Through every point of the full grid along the wall, each one straying the
+                // same way for all the walls going through it, so the walls still meet.
+                let (dx, dy) = (
+                    (b.0 as isize - a.0 as isize).signum(),
+                    (b.1 as isize - a.1 as isize).signum(),
+                );
+                let length = a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
+                let points: Vec<Point> = (0..=length)
+                    .map(|step| {
+                        let position = Position(
+                            (a.0 as isize + dx * step as isize) as usize,
+                            (a.1 as isize + dy * step as isize) as usize,
+                        );
+                        let point = match step {
+                            0 => start,
+                            step if step == length => end,
+                            _ => Point::new(position.0 as f32, position.1 as f32),
+                        };
+                        point + jitter(position)
+                    })
+                    .collect();
+
+                path.move_to(points[0]);
+                for pair in points[1..].windows(2) {
+                    let middle =
+                        Point::new((pair[0].x + pair[1].x) / 2.0, (pair[0].y + pair[1].y) / 2.0);
+                    path.quad_to(pair[0], middle);
+                }
+                path.line_to(points[length]);
+            }
+        }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style::new(Walls::default())
+    }
+}
+
+/// A small offset of a point of the full grid, always the same for the same point.
+fn jitter(position: Position) -> Point {
+    let Position(x, y) = position;
+    let mut rng = StdRng::seed_from_u64(((x as u64) << 32) | y as u64);
+    Point::new(
+        rng.gen_range(-SKETCH_JITTER..=SKETCH_JITTER),
+        rng.gen_range(-SKETCH_JITTER..=SKETCH_JITTER),
+    )
+}
+
+/// Where a row or a column of the full grid starts and ends, once walls are `wall_width` wide:
+/// the walls are on the even rows and columns, the corridors on the odd ones taking the rest.
+fn extent(coordinate: usize, wall_width: f32) -> (f32, f32) {
+    let middle = coordinate as f32;
+    match coordinate % 2 {
+        0 => (middle - wall_width / 2.0, middle + wall_width / 2.0),
+        _ => (
+            middle - 1.0 + wall_width / 2.0,
+            middle + 1.0 - wall_width / 2.0,
+        ),
+    }
+}
+
+impl Maze {
+    /// Draws the rounded corridors, carving them out of a block covering the cells of the maze.
+    /// The corridors are cleared from a layer, so whatever is under the maze shows through.
+    pub(super) fn draw_floor(&self, canvas: &mut Canvas, style: &Style) {
+        let (wall_width, corridor_width) = (style.wall_width(), style.corridor_width());
+        let (width, height) = (self.width * 2 + 1, self.height * 2 + 1);
+        let runs = |line: Vec<(usize, usize)>, is_part: &dyn Fn(CellType) -> bool| {
+            let mut runs = Vec::new();
+            let mut run: Option<(Position, Position)> = None;
+            for (x, y) in line {
+                match self.get_any_cell(x, y) {
+                    Some(cell) if is_part(cell.cell_type) => {
+                        let start = run.map(|(start, _)| start).unwrap_or(Position(x, y));
+                        run = Some((start, Position(x, y)));
+                    }
+                    _ => runs.extend(run.take()),
+                }
+            }
+            runs.extend(run);
+            runs
+        };
+        let rows = || (0..height).map(|y| (0..width).map(move |x| (x, y)).collect::<Vec<_>>());
+        let columns = || (0..width).map(|x| (0..height).map(move |y| (x, y)).collect::<Vec<_>>());
+
+        canvas.save_layer(&SaveLayerRec::default());
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_color(Palette::BLACK);
+        let mut block = Path::new();
+        for (a, b) in rows().flat_map(|row| runs(row, &|cell_type| cell_type != CellType::Outside))
+        {
+            let ((left, _), (_, right)) = (extent(a.0, wall_width), extent(b.0, wall_width));
+            let (top, bottom) = extent(a.1, wall_width);
+            block.add_rect(Rect::new(left, top, right, bottom), None);
+        }
+        canvas.draw_path(&block, &paint);
+
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(corridor_width);
+        paint.set_stroke_cap(PaintCap::Round);
+        paint.set_stroke_join(PaintJoin::Round);
+        paint.set_blend_mode(BlendMode::Clear);
+        let is_floor =
+            |cell_type: CellType| matches!(cell_type, CellType::Floor | CellType::Crossing(_));
+        let mut floor = Path::new();
+        let mut corridors = 0;
+        for (a, b) in rows()
+            .chain(columns())
+            .flat_map(|line| runs(line, &is_floor))
+        {
+            floor.move_to((a.0 as f32, a.1 as f32));
+            floor.line_to((b.0 as f32, b.1 as f32));
+            // Drawing huge mazes a piece at a time, instead of keeping all of their corridors.
+            corridors += 1;
+            if corridors % WALLS_PER_PATH == 0 {
+                canvas.draw_path(&floor, &paint);
+                floor.reset();
+            }
+        }
+        // Opening the doors on the sides out of the block.
+        if let Some(doors) = &self.doors {
+            for door in [doors.entry].iter().chain(doors.exits.iter()) {
+                if let Some((dx, dy)) = self.outward(*door) {
+                    let (x, y) = (door.0 as f32, door.1 as f32);
+                    floor.move_to((x, y));
+                    floor.line_to((x + dx, y + dy));
+                }
+            }
+        }
+        canvas.draw_path(&floor, &paint);
+
+        // The sides of the bridges, across the corridors going under them.
+        paint.set_stroke_cap(PaintCap::Butt);
+        let mut bridges = Path::new();
+        for (index, cell) in self.data.iter().enumerate() {
+            if let CellType::Crossing(over) = cell.cell_type {
+                let (x, y) = ((index % width) as f32, (index / width) as f32);
+                let (dx, dy) = match over {
+                    Axis::Vertical => (0.0, corridor_width / 2.0),
+                    Axis::Horizontal => (corridor_width / 2.0, 0.0),
+                };
+                bridges.move_to((x - dx, y - dy));
+                bridges.line_to((x + dx, y + dy));
+            }
+        }
+        paint.set_blend_mode(BlendMode::SrcOver);
+        paint.set_stroke_width(corridor_width + STROKE_WIDTH);
+        canvas.draw_path(&bridges, &paint);
+        paint.set_blend_mode(BlendMode::Clear);
+        paint.set_stroke_width(corridor_width);
+        canvas.draw_path(&bridges, &paint);
+
+        canvas.restore();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::style::*;
+
+    #[test]
+    fn shares_the_grid_between_walls_and_corridors() {
+        for walls in Walls::ALL {
+            assert_eq!(walls.name().parse::<Walls>().unwrap(), walls);
+
+            let style = Style::new(walls);
+            let wall_width = style.wall_width();
+            let ratio = style.corridor_width() / wall_width;
+            assert!((ratio - walls.corridor_ratio()).abs() < 1e-5);
+
+            // The rows of the full grid follow each other without gaps, every two of them
+            // taking two cells.
+            for coordinate in 0..10 {
+                let (_, end) = extent(coordinate, wall_width);
+                let (start, _) = extent(coordinate + 1, wall_width);
+                assert!((end - start).abs() < 1e-5);
+            }
+            let (start, _) = extent(2, wall_width);
+            let (_, end) = extent(3, wall_width);
+            assert!((end - start - 2.0).abs() < 1e-5);
+        }
+        assert!("pencil".parse::<Walls>().is_err());
+
+        // Sketched walls stray the same way every time they are drawn.
+        assert_eq!(jitter(Position(3, 4)), jitter(Position(3, 4)));
+        assert_ne!(jitter(Position(3, 4)), jitter(Position(4, 3)));
+    }
+}
@@ -0,0 +1,205 @@
+use super::grid::Grid;
+use super::packed::Bits;
+use super::solver::Graph;
+use super::{CellType, Maze, Position};
+
+/// Checks a maze is perfect: every cell can be reached from any other one, through a single
+/// path.
+pub(super) fn check_perfect<G: Grid>(grid: &G) -> Result<(), String> {
+    let cells = grid.cells();
+    let first = match cells.first() {
+        Some(first) => *first,
+        None => return Err("ERROR: the maze has no cells".to_string()),
+    };
+
+    let passages: usize = cells.iter().map(|cell| grid.passages(*cell).len()).sum();
+    if passages / 2 != cells.len() - 1 {
+        return Err(format!(
+            "ERROR: a perfect maze of {} cells has {} passages, not {}",
+            cells.len(),
+            cells.len() - 1,
+            passages / 2
+        ));
+    }
+
+    let mut seen = Bits::new(grid.index_count());
+    let mut stack = vec![first];
+    let mut reached = 0;
+    seen.set(grid.cell_index(first));
+    while let Some(cell) = stack.pop() {
+        reached += 1;
+        for next in grid.passages(cell) {
+            if !seen.get(grid.cell_index(next)) {
+                seen.set(grid.cell_index(next));
+                stack.push(next);
+            }
+        }
+    }
+    match reached == cells.len() {
+        true => Ok(()),
+        false => Err(format!(
+            "ERROR: only {} of the {} cells of the maze can be reached",
+            reached,
+            cells.len()
+        )),
+    }
+}
+
+impl Maze {
+    /// Checks the maze is perfect, with a single path between any two cells.
+    pub fn check_perfect(&self) -> Result<(), String> {
+        check_perfect(self)
+    }
+
+    /// Checks the doors of the maze are openings of its sides, or cells inside, and that its
+    /// solution walks from the entry to an exit without going through walls.
+    pub fn validate(&self) -> Result<(), String> {
+        let doors = match &self.doors {
+            Some(doors) => doors,
+            None => return Ok(()),
+        };
+        for door in [doors.entry].iter().chain(doors.exits.iter()) {
+            self.check_door(*door)?;
+        }
+
+        let path = match &self.solution {
+            Some(solution) => &solution.path,
+            None => return Ok(()),
+        };
+        if path.first() != Some(&doors.entry) {
+            return Err("ERROR: the solution doesn't start at the entry".to_string());
+        }
+        if !path
+            .last()
+            .map(|last| doors.exits.contains(last))
+            .unwrap_or(false)
+        {
+            return Err("ERROR: the solution doesn't end at an exit".to_string());
+        }
+        for step in path.windows(2) {
+            if !self.edges(step[0]).contains(&step[1]) {
+                let (Position(x_a, y_a), Position(x_b, y_b)) = (step[0], step[1]);
+                return Err(format!(
+                    "ERROR: the solution can't go from [{}, {}] to [{}, {}]",
+                    x_a, y_a, x_b, y_b
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks a door of the full grid is open, and either on a side that doesn't wrap around or
+    /// on a cell.
+    fn check_door(&self, door: Position) -> Result<(), String> {
+        let Position(x, y) = door;
+        let is_open = self
+            .get_any_cell(x, y)
+            .map(|cell| cell.cell_type == CellType::Floor)
+            .unwrap_or(false);
+        if !is_open {
+            return Err(format!("ERROR: the door [{}, {}] is not open", x, y));
+        }
+
+        let is_wrapped = (self.wrap.horizontal() && (x == 0 || x == self.width * 2))
+            || (self.wrap.vertical() && (y == 0 || y == self.height * 2));
+        let is_cell = x % 2 == 1 && y % 2 == 1;
+        match (self.outward(door), is_cell) {
+            (Some(_), _) if is_wrapped => Err(format!(
+                "ERROR: the door [{}, {}] is on a side that wraps around",
+                x, y
+            )),
+            (Some(_), _) | (None, true) => Ok(()),
+            (None, false) => Err(format!(
+                "ERROR: the door [{}, {}] is neither on a side nor on a cell",
+                x, y
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::validation::*;
+    use crate::maze::{Door, Generator, MazeOptions, Placement, Solver, Wrap};
+
+    #[test]
+    fn generates_valid_mazes_of_any_size() {
+        let sizes = [
+            (1, 1),
+            (1, 2),
+            (2, 1),
+            (1, 9),
+            (9, 1),
+            (2, 2),
+            (3, 7),
+            (12, 5),
+        ];
+        let placements = [
+            Placement::default(),
+            Placement::LongestPath,
+            Placement::Doors {
+                entry: Door::North,
+                exits: vec![Door::South, Door::Inside],
+            },
+        ];
+        for (width, height) in sizes {
+            for generator in Generator::ALL {
+                for (seed, placement) in (0..6).zip(placements.iter().cycle()) {
+                    let solver = Solver::ALL[seed as usize % Solver::ALL.len()];
+                    let options = MazeOptions {
+                        generator,
+                        solver,
+                        placement: placement.clone(),
+                        weave: generator.can_weave() && seed % 2 == 1,
+                        seed,
+                        ..MazeOptions::default()
+                    };
+                    let maze = Maze::new(width, height, &options).unwrap();
+                    let context = format!("{}x{} {} {}", width, height, generator, seed);
+                    maze.check_perfect().expect(&context);
+                    maze.validate().expect(&context);
+                    assert!(maze.solution().is_some(), "{}", context);
+
+                    let braided = Maze::new(
+                        width,
+                        height,
+                        &MazeOptions {
+                            braid: 1.0,
+                            ..options
+                        },
+                    );
+                    braided.unwrap().validate().expect(&context);
+                }
+            }
+        }
+
+        let options = MazeOptions {
+            generator: Generator::Wilson,
+            wrap: Wrap::Both,
+            placement: Placement::LongestPath,
+            ..MazeOptions::default()
+        };
+        let maze = Maze::new(5, 4, &options).unwrap();
+        maze.check_perfect().unwrap();
+        maze.validate().unwrap();
+
+        // A solution going through a wall.
+        let ascii = "\
+# # # # #
+S   #   E
+# # # # #
+";
+        let mut maze = Maze::from_ascii(ascii).unwrap();
+        maze.solution = Some(crate::maze::Solution {
+            path: (0..5).map(|x| Position(x, 1)).collect(),
+            visited: 5,
+        });
+        assert!(maze.validate().is_err());
+        assert!(maze.check_perfect().is_err());
+
+        assert!(Maze::new(0, 3, &MazeOptions::default()).is_err());
+        // Too many cells for the depth-first generator to keep on its stack.
+        assert!(Maze::new(1 << 16, 1 << 16, &MazeOptions::default()).is_err());
+    }
+}
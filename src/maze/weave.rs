@@ -0,0 +1,167 @@
+use super::{Cell, CellType, Maze, Position};
+
+/// The direction of a corridor of a square maze.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(super) enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl Axis {
+    /// The axis of the line between two positions of a row, or of a column.
+    pub(super) fn between(a: Position, b: Position) -> Axis {
+        match a.1 == b.1 {
+            true => Axis::Horizontal,
+            false => Axis::Vertical,
+        }
+    }
+
+    pub(super) fn across(&self) -> Axis {
+        match self {
+            Axis::Horizontal => Axis::Vertical,
+            Axis::Vertical => Axis::Horizontal,
+        }
+    }
+}
+
+/// The position in the middle of two positions two steps apart on a row, or on a column.
+pub(super) fn middle(a: Position, b: Position) -> Option<Position> {
+    match (a.0.abs_diff(b.0), a.1.abs_diff(b.1)) {
+        (2, 0) => Some(Position(a.0.min(b.0) + 1, a.1)),
+        (0, 2) => Some(Position(a.0, a.1.min(b.1) + 1)),
+        _ => None,
+    }
+}
+
+/// The position after `through`, coming from `from`.
+pub(super) fn beyond(from: Position, through: Position) -> Option<Position> {
+    Some(Position(
+        (through.0 * 2).checked_sub(from.0)?,
+        (through.1 * 2).checked_sub(from.1)?,
+    ))
+}
+
+impl Maze {
+    /// The axis of the corridor going over a cell, if it is a crossing.
+    pub(super) fn crossing(&self, position: Position) -> Option<Axis> {
+        self.crossing_at(position.0 * 2 + 1, position.1 * 2 + 1)
+    }
+
+    /// The axis of the corridor going over a position of the full grid, if it is a crossing.
+    pub(super) fn crossing_at(&self, true_x: usize, true_y: usize) -> Option<Axis> {
+        match self.get_any_cell(true_x, true_y)?.cell_type {
+            CellType::Crossing(over) => Some(over),
+            _ => None,
+        }
+    }
+
+    /// The cell on the other side of a neighbour, when a passage goes under the neighbour: a
+    /// crossing, or when weaving, a straight corridor going across.
+    pub(super) fn tunnel(&self, from: Position, through: Position) -> Option<Position> {
+        let axis = Axis::between(from, through);
+        match self.crossing(through) {
+            Some(over) if over != axis => beyond(from, through),
+            Some(_) => None,
+            None if self.weave => {
+                let to = beyond(from, through).filter(|to| self.is_inside(*to))?;
+                let across = match axis {
+                    Axis::Horizontal => [
+                        Position(through.0, through.1.checked_sub(1)?),
+                        Position(through.0, through.1 + 1),
+                    ],
+                    Axis::Vertical => [
+                        Position(through.0.checked_sub(1)?, through.1),
+                        Position(through.0 + 1, through.1),
+                    ],
+                };
+                let is_corridor = across
+                    .iter()
+                    .all(|side| self.is_inside(*side) && !self.is_wall_between(through, *side))
+                    && self.is_wall_between(from, through)
+                    && self.is_wall_between(through, to);
+
+                is_corridor.then_some(to)
+            }
+            None => None,
+        }
+    }
+
+    /// Opens a passage between two cells, going under the cell in the middle, which becomes a
+    /// crossing.
+    pub(super) fn cross(&mut self, from: Position, to: Position) {
+        if let Some(under) = middle(from, to) {
+            if let Some(cell) = self.get_floor_cell(under) {
+                let cell_type = CellType::Crossing(Axis::between(from, to).across());
+                self.set_floor_cell(under, Cell { cell_type, ..cell });
+            }
+            self.collapse_wall_between(from, under);
+            self.collapse_wall_between(under, to);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::grid::Grid;
+    use crate::maze::weave::*;
+    use crate::maze::{Generator, MazeOptions, Solver};
+
+    #[test]
+    fn weaves_perfect_mazes() {
+        for generator in [
+            Generator::DepthFirst,
+            Generator::Prim,
+            Generator::AldousBroder,
+            Generator::HuntAndKill,
+        ] {
+            let options = MazeOptions {
+                generator,
+                weave: true,
+                solver: Solver::AStar,
+                ..MazeOptions::default()
+            };
+            let maze = Maze::new(20, 15, &options).unwrap();
+
+            let crossings: Vec<Position> = maze
+                .cells()
+                .into_iter()
+                .filter(|cell| maze.crossing(*cell).is_some())
+                .collect();
+            assert!(!crossings.is_empty(), "{} doesn't weave", generator);
+
+            // Each crossing has a corridor going over it and one going under it.
+            for crossing in crossings {
+                let passages = maze.passages(crossing);
+                assert_eq!(passages.len(), 2);
+                let over = maze.crossing(crossing).unwrap();
+                assert!(passages.iter().all(|p| Axis::between(crossing, *p) == over));
+            }
+
+            // Every solver finds a way out, going over and under the bridges.
+            for solver in Solver::ALL {
+                let maze = Maze::new(
+                    20,
+                    15,
+                    &MazeOptions {
+                        solver,
+                        ..options.clone()
+                    },
+                )
+                .unwrap();
+                let path = &maze.solution().unwrap().path;
+                for step in path.windows(2) {
+                    match middle(step[0], step[1]) {
+                        Some(Position(x, y)) => {
+                            let under = Axis::between(step[0], step[1]);
+                            assert_eq!(maze.crossing_at(x, y), Some(under.across()));
+                        }
+                        None => assert_eq!(
+                            step[0].0.abs_diff(step[1].0) + step[0].1.abs_diff(step[1].1),
+                            1
+                        ),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use skia_safe::{Canvas, Paint};
+
+use super::{CellType, Door, Maze, MazeOptions, Placement, Position};
+use crate::utils::Palette;
+
+/// Size of the marks of the passages going through the wrapped sides, in cells of the full grid.
+const WRAP_MARKER_RADIUS: f32 = 0.6;
+
+/// Which sides of a square maze lead to the opposite ones, the passages leaving through one
+/// coming back through the other.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Wrap {
+    #[default]
+    None,
+    /// West and east, as around a cylinder.
+    Horizontal,
+    /// North and south, as around a cylinder lying down.
+    Vertical,
+    /// Every side, as around a torus.
+    Both,
+}
+
+impl Wrap {
+    pub const ALL: [Wrap; 4] = [Wrap::None, Wrap::Horizontal, Wrap::Vertical, Wrap::Both];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Wrap::None => "none",
+            Wrap::Horizontal => "horizontal",
+            Wrap::Vertical => "vertical",
+            Wrap::Both => "both",
+        }
+    }
+
+    pub fn horizontal(&self) -> bool {
+        matches!(self, Wrap::Horizontal | Wrap::Both)
+    }
+
+    pub fn vertical(&self) -> bool {
+        matches!(self, Wrap::Vertical | Wrap::Both)
+    }
+
+    /// Whether the side of a door leads to the opposite side, instead of out of the maze.
+    pub fn wraps(&self, door: Door) -> bool {
+        match door {
+            Door::West | Door::East => self.horizontal(),
+            Door::North | Door::South => self.vertical(),
+            Door::Inside => false,
+        }
+    }
+}
+
+impl Display for Wrap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Wrap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Wrap::ALL
+            .iter()
+            .find(|wrap| wrap.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Wrap::ALL.iter().map(|w| w.name()).collect();
+                format!(
+                    "ERROR: unknown wrap `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl Maze {
+    /// Checks the options let the maze wrap around, when it does.
+    pub(super) fn check_wrap(&self, is_masked: bool, options: &MazeOptions) -> Result<(), String> {
+        if self.wrap == Wrap::None {
+            return Ok(());
+        }
+        if options.weave {
+            return Err("ERROR: wrapped mazes can't weave their passages".to_string());
+        }
+        if is_masked {
+            return Err("ERROR: wrapped mazes can't follow the shape of a mask".to_string());
+        }
+        if options.generator.is_rectangular_only() {
+            return Err(format!(
+                "ERROR: the {} generator can't wrap mazes around",
+                options.generator
+            ));
+        }
+        if (self.wrap.horizontal() && self.width < 3) || (self.wrap.vertical() && self.height < 3) {
+            return Err(format!(
+                "ERROR: a maze needs 3 cells between its wrapped sides, not {}x{}",
+                self.width, self.height
+            ));
+        }
+        if let Placement::Doors { entry, exits } = &options.placement {
+            let doors = [entry].into_iter().chain(exits.iter());
+            if let Some(door) = doors.into_iter().find(|door| self.wrap.wraps(**door)) {
+                return Err(format!(
+                    "ERROR: the {} side wraps around, it has no doors",
+                    door
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The cells on the opposite sides of a cell on a wrapped side.
+    pub(super) fn wrapped_neighbours(&self, position: Position) -> Vec<Position> {
+        let Position(x, y) = position;
+        let mut neighbours = Vec::new();
+        if self.wrap.horizontal() {
+            if x == 0 {
+                neighbours.push(Position(self.width - 1, y));
+            }
+            if x + 1 == self.width {
+                neighbours.push(Position(0, y));
+            }
+        }
+        if self.wrap.vertical() {
+            if y == 0 {
+                neighbours.push(Position(x, self.height - 1));
+            }
+            if y + 1 == self.height {
+                neighbours.push(Position(x, 0));
+            }
+        }
+
+        neighbours
+    }
+
+    /// Whether two neighbours are on opposite sides, a wrapped side being between them.
+    pub(super) fn is_wrapped_between(&self, position_a: Position, position_b: Position) -> bool {
+        (self.wrap.horizontal() && position_a.0.abs_diff(position_b.0) > 1)
+            || (self.wrap.vertical() && position_a.1.abs_diff(position_b.1) > 1)
+    }
+
+    /// Position of the wall between two neighbours on the full grid. The walls of the wrapped
+    /// sides are the ones on the east, or south, side, and copied on the opposite side.
+    pub(super) fn wall_between(
+        &self,
+        position_a: Position,
+        position_b: Position,
+    ) -> (usize, usize) {
+        let across = |a: usize, b: usize, size: usize, wraps: bool| match wraps && a.abs_diff(b) > 1
+        {
+            true => size * 2,
+            false => a + b + 1,
+        };
+
+        (
+            across(
+                position_a.0,
+                position_b.0,
+                self.width,
+                self.wrap.horizontal(),
+            ),
+            across(
+                position_a.1,
+                position_b.1,
+                self.height,
+                self.wrap.vertical(),
+            ),
+        )
+    }
+
+    /// Which way a step between two positions of the full grid leaves through a wrapped side,
+    /// if it does.
+    pub(super) fn wrapped_step(&self, from: Position, to: Position) -> Option<(f32, f32)> {
+        let across = |a: usize, b: usize, wraps: bool| match (wraps && a.abs_diff(b) > 2, a > b) {
+            (true, true) => 1.0,
+            (true, false) => -1.0,
+            (false, _) => 0.0,
+        };
+        let direction = (
+            across(from.0, to.0, self.wrap.horizontal()),
+            across(from.1, to.1, self.wrap.vertical()),
+        );
+
+        (direction != (0.0, 0.0)).then_some(direction)
+    }
+
+    /// A disc on each wrapped side where a passage goes through it, split between the two
+    /// sides so copies of the maze side by side show it whole.
+    pub(super) fn draw_wrapped_passages(&self, canvas: &mut Canvas) {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_color(Palette::DARKER_BEIGE);
+
+        let (columns, rows) = (self.width * 2, self.height * 2);
+        let mut openings = Vec::new();
+        if self.wrap.horizontal() {
+            openings.extend((1..rows).step_by(2).flat_map(|y| [(0, y), (columns, y)]));
+        }
+        if self.wrap.vertical() {
+            openings.extend((1..columns).step_by(2).flat_map(|x| [(x, 0), (x, rows)]));
+        }
+        for (x, y) in openings {
+            if let Some(cell) = self.get_any_cell(x, y) {
+                if cell.cell_type == CellType::Floor {
+                    canvas.draw_circle((x as f32, y as f32), WRAP_MARKER_RADIUS, &paint);
+                }
+            }
+        }
+    }
+
+    /// The number of steps between two positions of the full grid, going across the wrapped
+    /// sides when it is shorter.
+    pub(super) fn distance(&self, from: Position, to: Position) -> usize {
+        let across = |a: usize, b: usize, size: usize, wraps: bool| {
+            let distance = a.abs_diff(b);
+            match wraps {
+                true => distance.min(size * 2 - distance),
+                false => distance,
+            }
+        };
+
+        across(from.0, to.0, self.width, self.wrap.horizontal())
+            + across(from.1, to.1, self.height, self.wrap.vertical())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::grid::Grid;
+    use crate::maze::wrap::*;
+    use crate::maze::{Generator, MazeOptions, Placement, Solver};
+
+    #[test]
+    fn wraps_around_the_sides() {
+        for generator in [Generator::Prim, Generator::Wilson, Generator::Kruskal] {
+            let options = MazeOptions {
+                generator,
+                wrap: Wrap::Both,
+                placement: Placement::LongestPath,
+                solver: Solver::AStar,
+                ..MazeOptions::default()
+            };
+            let maze = Maze::new(7, 5, &options).unwrap();
+
+            // A perfect maze, some of its passages going across the sides.
+            let cells = maze.cells();
+            let passages: usize = cells.iter().map(|cell| maze.passages(*cell).len()).sum();
+            assert_eq!(passages / 2, cells.len() - 1);
+            assert!(cells.iter().any(|cell| maze
+                .passages(*cell)
+                .iter()
+                .any(|passage| maze.is_wrapped_between(*cell, *passage))));
+
+            // The opposite sides are the same, so copies of the maze tile.
+            let (columns, rows) = (7 * 2, 5 * 2);
+            for y in 0..=rows {
+                let (west, east) = (maze.get_any_cell(0, y), maze.get_any_cell(columns, y));
+                assert!(west.unwrap().cell_type == east.unwrap().cell_type);
+            }
+            for x in 0..=columns {
+                let (north, south) = (maze.get_any_cell(x, 0), maze.get_any_cell(x, rows));
+                assert!(north.unwrap().cell_type == south.unwrap().cell_type);
+            }
+
+            // The solution steps from a cell to the next one, or across a side.
+            let path = &maze.solution().unwrap().path;
+            for step in path.windows(2) {
+                assert_eq!(maze.distance(step[0], step[1]), 1);
+            }
+
+            let from_json = Maze::from_json(&maze.to_json().unwrap()).unwrap();
+            assert_eq!(from_json.wrap, Wrap::Both);
+        }
+
+        let options = MazeOptions {
+            wrap: Wrap::Horizontal,
+            ..MazeOptions::default()
+        };
+        assert!(Maze::new(7, 5, &options).is_err());
+    }
+}
@@ -1,122 +1,983 @@
 use crate::geometry::{Segment, VectorMove};
-use crate::utils::{Bounded, Palette};
-use skia_safe::{Canvas, Color, Paint, PaintStyle, Path, Point};
+use crate::utils::{mix, Bounded, Drawable, Palette};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use skia_safe::{
+    AlphaType, CachingHint, Canvas, Color, ColorType, Data, Image, ImageInfo, Paint, PaintStyle,
+    Path, Point, Rect, Shader, TileMode,
+};
 
-pub fn draw(canvas: &mut Canvas) {
-    let mut paint = Paint::default();
-    paint.set_anti_alias(true);
-
-    let width = canvas.width();
-    let height = canvas.height();
-
-    paint.set_style(PaintStyle::Fill);
-    paint.set_stroke_width(height.min(width) / 100.0);
-    // Fill with the sky color.
-    canvas.clear(Palette::BEIGE);
-
-    canvas.save();
-
-    let base_origin = 0.47 * width;
-    let base_length = 0.26 * width;
-
-    canvas.translate(((width - base_length - base_origin) / 2.0, 0.0));
-
-    let base = Segment::new(
-        base_origin,
-        canvas.height(),
-        base_origin + base_length,
-        canvas.height(),
-    );
-    let summit = base
-        .point_at_position(0.45)
-        .move_along(base.normal(), -base.length() * 0.9);
-    draw_mountain(
-        canvas,
-        base,
-        summit,
-        Palette::GRAY,
-        Palette::WHITE,
-        &mut paint,
-    );
-
-    let base_origin = 0.26 * width;
-    let base_length = 0.34 * width;
-    let base = Segment::new(
-        base_origin,
-        canvas.height(),
-        base_origin + base_length,
-        canvas.height(),
-    );
-    let summit = base
-        .point_at_position(0.45)
-        .move_along(base.normal(), -base.length() * 0.9);
-    draw_mountain(
-        canvas,
-        base,
-        summit,
-        Palette::DARK_GRAY,
-        Palette::WHITE,
-        &mut paint,
-    );
-
-    let base_origin = 0.0 * width;
-    let base_length = 0.4 * width;
-    let base = Segment::new(
-        base_origin,
-        canvas.height(),
-        base_origin + base_length,
-        canvas.height(),
-    );
-    let summit = base
-        .point_at_position(0.45)
-        .move_along(base.normal(), -base.length() * 0.9);
-    draw_mountain(
-        canvas,
-        base,
-        summit,
-        Palette::BLACK,
-        Palette::WHITE,
-        &mut paint,
-    );
-
-    canvas.restore();
-    let sun_scale = width.min(height);
-    let sun_position = (canvas.width() - sun_scale * 0.32, sun_scale * 0.32);
-    paint.set_color(Palette::RED);
-    canvas.draw_circle(sun_position, sun_scale * 0.115, &paint);
-}
-
-fn draw_mountain(
-    canvas: &mut Canvas,
-    base: Segment,
-    summit: Point,
-    dark_color: Color,
+/// Number of midpoint displacement iterations, a ridgeline will have `2^n + 1` points.
+const RIDGE_ITERATIONS: u32 = 8;
+/// Fraction of the ridge above the snow line that is covered in snow.
+const SNOW_DEPTH: f32 = 0.6;
+/// How much the lit flank of a mountain takes the color of the light.
+const LIT_TINT: f32 = 0.25;
+/// How much darker the shaded flank of a mountain gets.
+const SHADE: f32 = 0.3;
+/// How much darker everything gets when there is no ambient light.
+const NIGHT_DARKNESS: f32 = 0.6;
+
+/// A triangle mountain. Positions along the base are fractions of the canvas width.
+#[derive(Clone, Debug)]
+pub struct Mountain {
+    pub base_origin: f32,
+    pub base_length: f32,
+    /// Position of the summit along the base, from 0.0 (left) to 1.0 (right).
+    pub summit_offset: f32,
+    /// Height of the summit, as a fraction of the base length.
+    pub summit_height: f32,
+    pub dark_color: Color,
+    pub snow_color: Color,
+    /// Position along the flanks where the snow starts, from 0.0 (base) to 1.0 (summit).
+    pub snow_line: f32,
+}
+
+/// The sun. Sizes are fractions of the smallest side of the canvas.
+#[derive(Clone, Debug)]
+pub struct Sun {
+    /// Position of the center, measured from the top right corner of the canvas.
+    pub position: (f32, f32),
+    pub radius: f32,
+    pub color: Color,
+}
+
+/// A skyline of mountains, drawn from the first to the last, and a sun.
+#[derive(Clone, Debug)]
+pub struct Landscape {
+    pub mountains: Vec<Mountain>,
+    pub sun: Sun,
+    /// When set, the sky, the sun and the lighting of the mountains follow the time of the day,
+    /// and the sun position of `sun` is ignored.
+    pub time: Option<TimeOfDay>,
+}
+
+/// Time of the day, in hours from 0.0 to 24.0. The sun rises at 6 on the left and sets at 18 on
+/// the right, then the moon takes its place for the night.
+#[derive(Copy, Clone, Debug)]
+pub struct TimeOfDay(pub f32);
+
+/// The light falling on the mountains.
+#[derive(Copy, Clone, Debug)]
+struct Light {
+    color: Color,
+    /// From -1.0 (lit from the left) to 1.0 (lit from the right), 0.0 being lit from above.
+    direction: f32,
+    /// From 0.0 (dark night) to 1.0 (broad daylight).
+    ambient: f32,
+}
+
+/// The look of the sky at a given hour, other hours being interpolated.
+struct Keyframe {
+    hour: f32,
+    sky_top: Color,
+    sky_bottom: Color,
     light_color: Color,
-    paint: &mut Paint,
-) {
-    paint.set_color(dark_color);
+    ambient: f32,
+}
+
+const NIGHT_SKY_TOP: Color = Palette::BLACK;
+const NIGHT_SKY_BOTTOM: Color = Palette::DARK_GRAY;
+const MOON_COLOR: Color = Palette::BEIGE;
+const DAWN_SKY_BOTTOM: Color = Color::new(0xfffcc0a8);
+const DUSK_SKY_BOTTOM: Color = Color::new(0xffe8876c);
+
+const KEYFRAMES: [Keyframe; 5] = [
+    Keyframe {
+        hour: 0.0,
+        sky_top: NIGHT_SKY_TOP,
+        sky_bottom: NIGHT_SKY_BOTTOM,
+        light_color: MOON_COLOR,
+        ambient: 0.0,
+    },
+    Keyframe {
+        hour: 6.0,
+        sky_top: Palette::DARK_BEIGE,
+        sky_bottom: DAWN_SKY_BOTTOM,
+        light_color: DAWN_SKY_BOTTOM,
+        ambient: 0.6,
+    },
+    Keyframe {
+        hour: 12.0,
+        sky_top: Palette::BEIGE,
+        sky_bottom: Palette::BEIGE,
+        light_color: Palette::RED,
+        ambient: 1.0,
+    },
+    Keyframe {
+        hour: 18.0,
+        sky_top: Palette::DARKER_BEIGE,
+        sky_bottom: DUSK_SKY_BOTTOM,
+        light_color: Palette::RED,
+        ambient: 0.6,
+    },
+    Keyframe {
+        hour: 24.0,
+        sky_top: NIGHT_SKY_TOP,
+        sky_bottom: NIGHT_SKY_BOTTOM,
+        light_color: MOON_COLOR,
+        ambient: 0.0,
+    },
+];
+
+impl TimeOfDay {
+    fn hour(&self) -> f32 {
+        self.0.rem_euclid(24.0)
+    }
+
+    /// The keyframes surrounding the current hour, and the ratio between the two.
+    fn keyframes(&self) -> (&Keyframe, &Keyframe, f32) {
+        let hour = self.hour();
+        let next = KEYFRAMES
+            .iter()
+            .position(|keyframe| keyframe.hour > hour)
+            .unwrap_or(KEYFRAMES.len() - 1);
+        let (from, to) = (&KEYFRAMES[next - 1], &KEYFRAMES[next]);
+
+        (from, to, (hour - from.hour) / (to.hour - from.hour))
+    }
+
+    fn is_day(&self) -> bool {
+        (6.0..18.0).contains(&self.hour())
+    }
+
+    /// Progress of the sun, or of the moon at night, along its arc, from 0.0 (rising on the
+    /// left) to 1.0 (setting on the right).
+    fn arc_progress(&self) -> f32 {
+        ((self.hour() + 18.0) % 12.0) / 12.0
+    }
+
+    pub fn sky(&self) -> Sky {
+        let (from, to, ratio) = self.keyframes();
+        Sky::Gradient(
+            mix(from.sky_top, to.sky_top, ratio),
+            mix(from.sky_bottom, to.sky_bottom, ratio),
+        )
+    }
+
+    /// The color of the sun, or of the moon at night.
+    fn celestial_color(&self, sun_color: Color) -> Color {
+        if !self.is_day() {
+            return MOON_COLOR;
+        }
+        // The sun is redder close to the horizon.
+        let elevation = (self.arc_progress() * std::f32::consts::PI).sin();
+        mix(DUSK_SKY_BOTTOM, sun_color, elevation)
+    }
+
+    /// Position of the sun, or of the moon at night, along its arc.
+    fn celestial_position(&self, width: f32, height: f32) -> Point {
+        let progress = self.arc_progress();
+        Point::new(
+            width * (0.1 + 0.8 * progress),
+            height * (0.85 - 0.7 * (progress * std::f32::consts::PI).sin()),
+        )
+    }
+
+    fn light(&self) -> Light {
+        let (from, to, ratio) = self.keyframes();
+        let direction = (self.arc_progress() - 0.5) * 2.0;
+
+        Light {
+            color: mix(from.light_color, to.light_color, ratio),
+            // The moon light is too weak for strong shadows.
+            direction: if self.is_day() {
+                direction
+            } else {
+                direction * 0.5
+            },
+            ambient: from.ambient + (to.ambient - from.ambient) * ratio,
+        }
+    }
+}
+
+impl Light {
+    fn darken(&self, color: Color, shade: f32) -> Color {
+        mix(
+            color,
+            Palette::BLACK,
+            shade + (1.0 - self.ambient) * NIGHT_DARKNESS,
+        )
+    }
+
+    fn lit(&self, color: Color) -> Color {
+        self.darken(mix(color, self.color, LIT_TINT * self.direction.abs()), 0.0)
+    }
+
+    fn shaded(&self, color: Color) -> Color {
+        self.darken(color, SHADE * self.direction.abs())
+    }
+}
+
+impl Landscape {
+    /// The three snowy peaks and the red sun of the original painting.
+    pub fn three_peaks() -> Self {
+        let mountain = |base_origin: f32, base_length: f32, dark_color: Color| Mountain {
+            base_origin,
+            base_length,
+            summit_offset: 0.45,
+            summit_height: 0.9,
+            dark_color,
+            snow_color: Palette::WHITE,
+            snow_line: 0.6,
+        };
+
+        Landscape {
+            mountains: vec![
+                mountain(0.47, 0.26, Palette::GRAY),
+                mountain(0.26, 0.34, Palette::DARK_GRAY),
+                mountain(0.0, 0.4, Palette::BLACK),
+            ],
+            sun: Sun::default(),
+            time: None,
+        }
+    }
+
+    pub fn at(mut self, time: TimeOfDay) -> Self {
+        self.time = Some(time);
+        self
+    }
+}
+
+impl Default for Sun {
+    fn default() -> Self {
+        Sun {
+            position: (0.32, 0.32),
+            radius: 0.115,
+            color: Palette::RED,
+        }
+    }
+}
+
+impl Drawable for Landscape {
+    fn draw(&self, canvas: &mut Canvas) {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+
+        let width = canvas.width();
+        let height = canvas.height();
+
+        paint.set_style(PaintStyle::Fill);
+        paint.set_stroke_width(height.min(width) / 100.0);
+        // Fill with the sky color. Following the time of the day, the sun is behind the
+        // mountains, so it can set.
+        let light = match &self.time {
+            None => {
+                canvas.clear(Palette::BEIGE);
+                None
+            }
+            Some(time) => {
+                time.sky().draw(canvas);
+                paint.set_color(time.celestial_color(self.sun.color));
+                canvas.draw_circle(
+                    time.celestial_position(width, height),
+                    width.min(height) * self.sun.radius,
+                    &paint,
+                );
+                Some(time.light())
+            }
+        };
+
+        canvas.save();
+
+        // Center the skyline horizontally.
+        let left = self
+            .mountains
+            .iter()
+            .map(|mountain| mountain.base_origin)
+            .fold(f32::INFINITY, f32::min);
+        let right = self
+            .mountains
+            .iter()
+            .map(|mountain| mountain.base_origin + mountain.base_length)
+            .fold(f32::NEG_INFINITY, f32::max);
+        if left <= right {
+            canvas.translate(((width - (right + left) * width) / 2.0, 0.0));
+        }
+
+        for mountain in self.mountains.iter() {
+            mountain.draw_lit(canvas, &mut paint, light.as_ref());
+        }
+
+        canvas.restore();
+
+        if self.time.is_none() {
+            self.sun.draw_with_paint(canvas, &mut paint);
+        }
+    }
+}
+
+impl Mountain {
+    /// Draws the mountain with its snow cap. Without light, both flanks are plain; with light,
+    /// the whole mountain is shaded, then the flank facing the light is drawn again, lit.
+    fn draw_lit(&self, canvas: &mut Canvas, paint: &mut Paint, light: Option<&Light>) {
+        let base_origin = self.base_origin * canvas.width();
+        let base_length = self.base_length * canvas.width();
+        let base = Segment::new(
+            base_origin,
+            canvas.height(),
+            base_origin + base_length,
+            canvas.height(),
+        );
+        let summit = base
+            .point_at_position(self.summit_offset)
+            .move_along(base.normal(), -base.length() * self.summit_height);
+
+        let mut body: Path = Path::new();
+        body.move_to(base.a());
+        body.line_to(base.b());
+
+        body.line_to(summit);
+        body.close();
+
+        let snow_start = Segment::from_points(base.a(), summit).point_at_position(self.snow_line);
+        let side = Segment::from_points(base.b(), summit).point_at_position(self.snow_line);
+
+        let mut snow = Path::new();
+        snow.move_to(snow_start);
+        snow.line_to(summit);
+        snow.line_to(Segment::from_points(summit, side).point_at_position(SNOW_DEPTH));
+        snow.line_to(Segment::from_points(snow_start, side).point_at_position(SNOW_DEPTH));
+        snow.close();
+
+        let light = match light {
+            None => {
+                paint.set_color(self.dark_color);
+                canvas.draw_path(&body, paint);
+                paint.set_color(self.snow_color);
+                canvas.draw_path(&snow, paint);
+                return;
+            }
+            Some(light) => light,
+        };
+
+        paint.set_color(light.shaded(self.dark_color));
+        canvas.draw_path(&body, paint);
+        paint.set_color(light.shaded(self.snow_color));
+        canvas.draw_path(&snow, paint);
 
-    let mut path: Path = Path::new();
-    path.move_to(base.a());
-    path.line_to(base.b());
+        let lit_flank = if light.direction < 0.0 {
+            Rect::new(base.a().x, summit.y, summit.x, base.a().y)
+        } else {
+            Rect::new(summit.x, summit.y, base.b().x, base.b().y)
+        };
 
-    path.line_to(summit);
-    path.close();
+        canvas.save();
+        canvas.clip_rect(lit_flank, None, true);
+        paint.set_color(light.lit(self.dark_color));
+        canvas.draw_path(&body, paint);
+        paint.set_color(light.lit(self.snow_color));
+        canvas.draw_path(&snow, paint);
+        canvas.restore();
+    }
+}
+
+impl Drawable for Mountain {
+    fn draw(&self, canvas: &mut Canvas) {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Fill);
+        self.draw_with_paint(canvas, &mut paint);
+    }
+
+    fn draw_with_paint(&self, canvas: &mut Canvas, paint: &mut Paint) {
+        self.draw_lit(canvas, paint, None);
+    }
+}
+
+impl Drawable for Sun {
+    fn draw(&self, canvas: &mut Canvas) {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        self.draw_with_paint(canvas, &mut paint);
+    }
+
+    fn draw_with_paint(&self, canvas: &mut Canvas, paint: &mut Paint) {
+        let sun_scale = canvas.width().min(canvas.height());
+        let sun_position = (
+            canvas.width() - sun_scale * self.position.0,
+            sun_scale * self.position.1,
+        );
+        paint.set_color(self.color);
+        canvas.draw_circle(sun_position, sun_scale * self.radius, paint);
+    }
+}
+
+pub fn draw(canvas: &mut Canvas) {
+    Landscape::three_peaks().draw(canvas);
+}
+
+pub fn draw_dusk(canvas: &mut Canvas) {
+    Landscape::three_peaks().at(TimeOfDay(16.5)).draw(canvas);
+}
 
-    canvas.draw_path(&path, paint);
+pub fn draw_night(canvas: &mut Canvas) {
+    Landscape::three_peaks().at(TimeOfDay(22.0)).draw(canvas);
+}
+
+/// A layer of a procedural mountain range. Altitudes are fractions of the canvas height, measured
+/// from the bottom of the canvas.
+#[derive(Clone, Debug)]
+pub struct RangeLayer {
+    pub color: Color,
+    pub snow_color: Color,
+    /// Altitude of both ends of the ridgeline, before displacement.
+    pub altitude: f32,
+    /// Maximum displacement of the first midpoint.
+    pub displacement: f32,
+    /// Factor applied to the displacement at each iteration, the lower the smoother.
+    pub roughness: f32,
+    /// The ridge is covered in snow above that altitude, `None` for no snow at all.
+    pub snow_line: Option<f32>,
+}
+
+/// The sky behind a landscape.
+#[derive(Clone, Debug)]
+pub enum Sky {
+    Flat(Color),
+    /// A vertical gradient, from the top of the canvas to the bottom.
+    Gradient(Color, Color),
+}
 
-    {
-        let snow_start = Segment::from_points(base.a(), summit).point_at_position(0.60);
-        let side = Segment::from_points(base.b(), summit).point_at_position(0.60);
+/// A band of fog drawn at the feet of each layer of a range but the front one, fading upwards.
+#[derive(Clone, Debug)]
+pub struct Fog {
+    pub color: Color,
+    /// Height of the fading part of the band, as a fraction of the canvas height.
+    pub height: f32,
+}
+
+/// A seeded range of mountains, whose ridgelines are generated by midpoint displacement.
+pub struct MountainRange {
+    /// Layers, from the background to the foreground.
+    layers: Vec<RangeLayer>,
+    ridgelines: Vec<Vec<f32>>,
+    fog: Option<Fog>,
+}
+
+impl MountainRange {
+    pub fn new(layers: Vec<RangeLayer>, mut rng: StdRng) -> Self {
+        let ridgelines = layers
+            .iter()
+            .map(|layer| MountainRange::ridgeline(layer, &mut rng))
+            .collect();
+
+        MountainRange {
+            layers,
+            ridgelines,
+            fog: None,
+        }
+    }
+
+    /// Uses existing elevation profiles as ridgelines instead of generating them, one profile per
+    /// layer. Each profile goes from `altitude` at its lowest to `altitude + displacement` at its
+    /// highest, elevations being expected between 0.0 and 1.0.
+    pub fn from_profiles(layers: Vec<RangeLayer>, profiles: Vec<Vec<f32>>) -> Self {
+        let ridgelines = layers
+            .iter()
+            .zip(profiles.iter())
+            .map(|(layer, profile)| {
+                profile
+                    .iter()
+                    .map(|elevation| {
+                        (layer.altitude + elevation * layer.displacement).clamp(0.0, 1.0)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        MountainRange {
+            layers,
+            ridgelines,
+            fog: None,
+        }
+    }
+
+    pub fn with_fog(mut self, fog: Fog) -> Self {
+        self.fog = Some(fog);
+        self
+    }
+
+    /// Any number of layers, fading into the haze color as they get further away, like the
+    /// atmosphere would do. Further layers also get smaller and higher, for a parallax effect.
+    pub fn hazy_layers(count: usize, foreground: Color, haze: Color) -> Vec<RangeLayer> {
+        (0..count)
+            .map(|index| {
+                // From 1 / count for the furthest layer, which keeps a trace of the foreground
+                // color against the haze, to 1.0 for the closest.
+                let closeness = (index + 1) as f32 / count as f32;
+                let haziness = 1.0 - closeness;
+
+                RangeLayer {
+                    color: mix(foreground, haze, haziness),
+                    snow_color: mix(Palette::WHITE, haze, haziness),
+                    altitude: 0.05 + 0.4 * haziness,
+                    displacement: 0.12 + 0.28 * closeness,
+                    roughness: 0.5,
+                    snow_line: Some(0.5),
+                }
+            })
+            .collect()
+    }
+
+    /// The gray, dark gray and black look of the original painting.
+    pub fn three_layers() -> Vec<RangeLayer> {
+        vec![
+            RangeLayer {
+                color: Palette::GRAY,
+                snow_color: Palette::WHITE,
+                altitude: 0.32,
+                displacement: 0.22,
+                roughness: 0.55,
+                snow_line: Some(0.42),
+            },
+            RangeLayer {
+                color: Palette::DARK_GRAY,
+                snow_color: Palette::WHITE,
+                altitude: 0.22,
+                displacement: 0.3,
+                roughness: 0.5,
+                snow_line: Some(0.38),
+            },
+            RangeLayer {
+                color: Palette::BLACK,
+                snow_color: Palette::WHITE,
+                altitude: 0.1,
+                displacement: 0.4,
+                roughness: 0.45,
+                snow_line: Some(0.4),
+            },
+        ]
+    }
+
+    /// Generates the altitudes of a ridgeline, evenly spaced from the left to the right of the
+    /// canvas, following the midpoint displacement algorithm
+    /// (https://en.wikipedia.org/wiki/Diamond-square_algorithm#Midpoint_displacement_algorithm).
+    fn ridgeline(layer: &RangeLayer, rng: &mut StdRng) -> Vec<f32> {
+        let count = 2usize.pow(RIDGE_ITERATIONS) + 1;
+        let mut altitudes = vec![layer.altitude; count];
+
+        let mut step = count - 1;
+        let mut displacement = layer.displacement;
+        while step > 1 {
+            let half = step / 2;
+            for start in (0..count - 1).step_by(step) {
+                let middle = (altitudes[start] + altitudes[start + step]) / 2.0;
+                altitudes[start + half] = middle + rng.gen_range(-1.0..1.0) * displacement;
+            }
+            displacement *= layer.roughness;
+            step = half;
+        }
+
+        altitudes
+            .iter()
+            .map(|altitude| altitude.clamp(0.0, 1.0))
+            .collect()
+    }
+}
+
+impl Drawable for MountainRange {
+    fn draw(&self, canvas: &mut Canvas) {
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Fill);
+
+        let width = canvas.width();
+        let height = canvas.height();
+        let to_point = |index: usize, count: usize, altitude: f32| {
+            Point::new(
+                index as f32 / (count - 1) as f32 * width,
+                height * (1.0 - altitude),
+            )
+        };
+
+        let layer_count = self.layers.len();
+        for (index, (layer, ridgeline)) in
+            self.layers.iter().zip(self.ridgelines.iter()).enumerate()
+        {
+            let count = ridgeline.len();
+
+            let mut path = Path::new();
+            path.move_to((0.0, height));
+            for (index, altitude) in ridgeline.iter().enumerate() {
+                path.line_to(to_point(index, count, *altitude));
+            }
+            path.line_to((width, height));
+            path.close();
+
+            paint.set_color(layer.color);
+            canvas.draw_path(&path, &paint);
+
+            if let Some(snow_line) = layer.snow_line {
+                // The snow follows the ridge, and gets thinner down to the snow line.
+                let mut path = Path::new();
+                path.move_to(to_point(0, count, ridgeline[0]));
+                for (index, altitude) in ridgeline.iter().enumerate().skip(1) {
+                    path.line_to(to_point(index, count, *altitude));
+                }
+                for (index, altitude) in ridgeline.iter().enumerate().rev() {
+                    let bottom = if *altitude > snow_line {
+                        altitude - (altitude - snow_line) * SNOW_DEPTH
+                    } else {
+                        *altitude
+                    };
+                    path.line_to(to_point(index, count, bottom));
+                }
+                path.close();
+
+                paint.set_color(layer.snow_color);
+                canvas.draw_path(&path, &paint);
+            }
+
+            // Fog sits between this layer and the next one, none being in front of the last.
+            let fog = self.fog.as_ref().filter(|_| index + 1 < layer_count);
+            if let Some(fog) = fog {
+                // A band fading upwards to the altitude of both ends of the ridge.
+                let top = height * (1.0 - layer.altitude);
+                let bottom = top + fog.height * height;
+                let colors = [fog.color.with_a(0), fog.color];
+                let mut fog_paint = Paint::default();
+                fog_paint.set_shader(Shader::linear_gradient(
+                    ((0.0, top), (0.0, bottom)),
+                    &colors[..],
+                    None,
+                    TileMode::Clamp,
+                    None,
+                    None,
+                ));
+                canvas.draw_rect(Rect::new(0.0, top, width, bottom), &fog_paint);
+            }
+        }
+    }
+}
+
+impl Drawable for Sky {
+    fn draw(&self, canvas: &mut Canvas) {
+        match self {
+            Sky::Flat(color) => {
+                canvas.clear(*color);
+            }
+            Sky::Gradient(top, bottom) => {
+                let colors = [*top, *bottom];
+                let mut paint = Paint::default();
+                paint.set_shader(Shader::linear_gradient(
+                    ((0.0, 0.0), (0.0, canvas.height())),
+                    &colors[..],
+                    None,
+                    TileMode::Clamp,
+                    None,
+                    None,
+                ));
+                canvas.draw_paint(&paint);
+            }
+        }
+    }
+}
+
+pub fn draw_range(canvas: &mut Canvas) {
+    // Using a set seed to have a reproducable range.
+    let rng = StdRng::seed_from_u64(42);
+
+    Sky::Flat(Palette::BEIGE).draw(canvas);
+    MountainRange::new(MountainRange::three_layers(), rng).draw(canvas);
+    Sun::default().draw(canvas);
+}
+
+/// A grid of elevations, normalized from 0.0 (lowest) to 1.0 (highest). Rows go from the back
+/// (top of the map) to the front (bottom of the map).
+pub struct Heightmap {
+    width: usize,
+    height: usize,
+    elevations: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Reads a grayscale image, any format supported by skia, the lighter the higher.
+    pub fn from_image(bytes: &[u8]) -> Result<Self, String> {
+        let image = Image::from_encoded(Data::new_copy(bytes))
+            .ok_or_else(|| "ERROR: couldn't decode the heightmap image".to_string())?;
+        let (width, height) = (image.width() as usize, image.height() as usize);
+
+        let info = ImageInfo::new(
+            (width as i32, height as i32),
+            ColorType::Gray8,
+            AlphaType::Opaque,
+            None,
+        );
+        let mut pixels = vec![0u8; width * height];
+        if !image.read_pixels(&info, &mut pixels, width, (0, 0), CachingHint::Allow) {
+            return Err("ERROR: couldn't read the heightmap pixels".to_string());
+        }
+
+        Heightmap::new(
+            width,
+            height,
+            pixels.iter().map(|pixel| *pixel as f32).collect(),
+        )
+    }
+
+    /// Reads a CSV file where each line is a row of elevations. A single elevation profile, like
+    /// an exported skyline, is a heightmap of one row: it can either be written on one line, one
+    /// elevation per line, or under a `distance,elevation` header, one point per line with
+    /// increasing distances. Only a header on the first line may hold something else than
+    /// numbers.
+    pub fn from_csv(csv: &str) -> Result<Self, String> {
+        let parse = |line: &str| {
+            line.split(',')
+                .map(|value| value.trim().parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+        };
+
+        let mut lines = csv
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .peekable();
+        let header = lines
+            .next_if(|(_, line)| parse(line).is_err())
+            .map(|(_, line)| line.to_lowercase().replace(' ', ""));
+        let rows = lines
+            .map(|(index, line)| {
+                parse(line).map_err(|_e| {
+                    format!(
+                        "ERROR: line {} of the heightmap is not a list of numbers: `{}`",
+                        index + 1,
+                        line
+                    )
+                })
+            })
+            .collect::<Result<Vec<Vec<f32>>, String>>()?;
+
+        if header.as_deref() == Some("distance,elevation") {
+            if rows.iter().any(|row| row.len() != 2) {
+                return Err(
+                    "ERROR: each point of a profile must be a distance and an elevation"
+                        .to_string(),
+                );
+            }
+            if rows.windows(2).any(|pair| pair[0][0] >= pair[1][0]) {
+                return Err("ERROR: the distances of a profile must increase".to_string());
+            }
+            let profile: Vec<f32> = rows.iter().map(|row| row[1]).collect();
+            return Heightmap::new(profile.len(), 1, profile);
+        }
+        if rows.len() > 1 && rows.iter().all(|row| row.len() == 1) {
+            return Heightmap::new(rows.len(), 1, rows.concat());
+        }
+
+        let width = rows.first().map(|row| row.len()).unwrap_or(0);
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(
+                "ERROR: all the rows of the heightmap must have the same length".to_string(),
+            );
+        }
+
+        Heightmap::new(width, rows.len(), rows.concat())
+    }
+
+    fn new(width: usize, height: usize, elevations: Vec<f32>) -> Result<Self, String> {
+        if width < 2 || height == 0 {
+            return Err("ERROR: a heightmap needs at least two elevations per row".to_string());
+        }
+
+        let lowest = elevations.iter().cloned().fold(f32::INFINITY, f32::min);
+        let highest = elevations.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let range = if highest > lowest {
+            highest - lowest
+        } else {
+            1.0
+        };
+
+        Ok(Heightmap {
+            width,
+            height,
+            elevations: elevations
+                .iter()
+                .map(|elevation| (elevation - lowest) / range)
+                .collect(),
+        })
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn row(&self, row: usize) -> Option<Vec<f32>> {
+        if row >= self.height {
+            return None;
+        }
+
+        Some(self.elevations[row * self.width..(row + 1) * self.width].to_vec())
+    }
+
+    /// Picks `count` rows evenly spread from the back to the front of the map.
+    pub fn spread_rows(&self, count: usize) -> Vec<usize> {
+        (0..count)
+            .map(|index| (index + 1) * self.height / (count + 1))
+            .collect()
+    }
+}
+
+/// Draws layered silhouettes sampled along the given rows of a heightmap, from the back to the
+/// front.
+pub fn draw_heightmap(canvas: &mut Canvas, heightmap: &Heightmap, rows: &[usize]) {
+    let profiles = rows
+        .iter()
+        .filter_map(|row| heightmap.row(*row))
+        .collect::<Vec<_>>();
+    let layers = if profiles.len() == 3 {
+        MountainRange::three_layers()
+    } else {
+        MountainRange::hazy_layers(profiles.len(), Palette::BLACK, Palette::BEIGE)
+    };
+
+    Sky::Flat(Palette::BEIGE).draw(canvas);
+    Sun::default().draw(canvas);
+    MountainRange::from_profiles(layers, profiles).draw(canvas);
+}
+
+pub fn draw_hazy_range(canvas: &mut Canvas) {
+    // Using a set seed to have a reproducable range.
+    let rng = StdRng::seed_from_u64(42);
+
+    Sky::Gradient(Palette::DARK_BEIGE, Palette::BEIGE).draw(canvas);
+    Sun::default().draw(canvas);
+    MountainRange::new(
+        MountainRange::hazy_layers(6, Palette::BLACK, Palette::BEIGE),
+        rng,
+    )
+    .with_fog(Fog {
+        color: Palette::BEIGE,
+        height: 0.08,
+    })
+    .draw(canvas);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mountain::*;
+
+    #[test]
+    fn ridgeline_displaces_midpoints() {
+        let layer = RangeLayer {
+            color: Palette::BLACK,
+            snow_color: Palette::WHITE,
+            altitude: 0.3,
+            displacement: 0.2,
+            roughness: 0.5,
+            snow_line: None,
+        };
+        let ridgeline = MountainRange::ridgeline(&layer, &mut StdRng::seed_from_u64(42));
+
+        assert_eq!(2usize.pow(RIDGE_ITERATIONS) + 1, ridgeline.len());
+        // The ends keep their altitude, and the first midpoint strays at most by the displacement.
+        let last = ridgeline.len() - 1;
+        assert_eq!(0.3, ridgeline[0]);
+        assert_eq!(0.3, ridgeline[last]);
+        assert!((ridgeline[last / 2] - 0.3).abs() <= 0.2);
+        // The displacements add up to at most `displacement / (1 - roughness)`.
+        assert!(ridgeline
+            .iter()
+            .all(|altitude| (altitude - 0.3).abs() <= 0.4));
+        assert!(ridgeline.iter().any(|altitude| *altitude != 0.3));
+
+        // The same seed draws the same ridge.
+        let again = MountainRange::ridgeline(&layer, &mut StdRng::seed_from_u64(42));
+        assert_eq!(ridgeline, again);
+
+        // Without displacement, the ridge stays flat.
+        let flat = RangeLayer {
+            displacement: 0.0,
+            ..layer.clone()
+        };
+        let ridgeline = MountainRange::ridgeline(&flat, &mut StdRng::seed_from_u64(42));
+        assert!(ridgeline.iter().all(|altitude| *altitude == 0.3));
+
+        // Ridges too high are clamped to the canvas.
+        let high = RangeLayer {
+            altitude: 0.95,
+            displacement: 0.5,
+            ..layer
+        };
+        let ridgeline = MountainRange::ridgeline(&high, &mut StdRng::seed_from_u64(42));
+        assert!(ridgeline
+            .iter()
+            .all(|altitude| (0.0..=1.0).contains(altitude)));
+    }
+
+    #[test]
+    fn interpolates_keyframes() {
+        let (from, to, ratio) = TimeOfDay(9.0).keyframes();
+        assert_eq!((6.0, 12.0, 0.5), (from.hour, to.hour, ratio));
+        let (from, to, ratio) = TimeOfDay(0.0).keyframes();
+        assert_eq!((0.0, 6.0, 0.0), (from.hour, to.hour, ratio));
+        // Hours wrap around the day.
+        assert_eq!(1.0, TimeOfDay(25.0).hour());
+        assert_eq!(23.0, TimeOfDay(-1.0).hour());
+
+        // The ambient light goes from the dark night to the broad daylight, and back.
+        let ambient = |hour| TimeOfDay(hour).light().ambient;
+        assert_eq!(0.0, ambient(0.0));
+        assert_eq!(0.3, ambient(3.0));
+        assert_eq!(0.8, ambient(9.0));
+        assert_eq!(1.0, ambient(12.0));
+        assert_eq!(0.6, ambient(18.0));
+        assert_eq!(0.0, ambient(24.0));
+
+        // Keyframes are reached exactly, and the colors halfway between.
+        assert_eq!(Palette::RED, TimeOfDay(12.0).light().color);
+        assert_eq!(MOON_COLOR, TimeOfDay(0.0).light().color);
+        assert_eq!(
+            mix(DAWN_SKY_BOTTOM, Palette::RED, 0.5),
+            TimeOfDay(9.0).light().color
+        );
+
+        // The sun rises on the left at 6, is overhead at 12 and sets on the right at 18.
+        assert!(TimeOfDay(6.0).is_day() && !TimeOfDay(18.0).is_day());
+        assert_eq!(-1.0, TimeOfDay(6.0).light().direction);
+        assert_eq!(0.0, TimeOfDay(12.0).light().direction);
+        // The moon casts softer shadows.
+        assert_eq!(-0.5, TimeOfDay(18.0).light().direction);
+        assert_eq!(0.0, TimeOfDay(0.0).light().direction);
+    }
+
+    #[test]
+    fn heightmap_from_csv_rows() {
+        let heightmap = Heightmap::from_csv("0,5,10\n10,0,5\n").unwrap();
+
+        assert_eq!(2, heightmap.height());
+        assert_eq!(Some(vec![0.0, 0.5, 1.0]), heightmap.row(0));
+        assert_eq!(Some(vec![1.0, 0.0, 0.5]), heightmap.row(1));
+        assert_eq!(None, heightmap.row(2));
+    }
+
+    #[test]
+    fn heightmap_from_csv_profile() {
+        let heightmap =
+            Heightmap::from_csv("distance,elevation\n0,1200\n100,1600\n200,2000\n").unwrap();
+
+        assert_eq!(1, heightmap.height());
+        assert_eq!(Some(vec![0.0, 0.5, 1.0]), heightmap.row(0));
+    }
+
+    #[test]
+    fn heightmap_from_csv_uneven_rows() {
+        assert!(Heightmap::from_csv("0,5,10\n10,0\n").is_err());
+        // Only a header may not be numbers.
+        assert!(Heightmap::from_csv("0,5,10\n1,x,3\n").is_err());
+        assert!(Heightmap::from_csv("distance,elevation\n0,1\n100,2,3\n").is_err());
+        assert!(Heightmap::from_csv("distance,elevation\n100,1\n0,2\n").is_err());
+    }
 
-        let mut path = Path::new();
-        path.move_to(snow_start);
-        path.line_to(summit);
-        path.line_to(Segment::from_points(summit, side).point_at_position(0.60));
-        path.line_to(Segment::from_points(snow_start, side).point_at_position(0.60));
-        path.close();
+    #[test]
+    fn heightmap_from_csv_columns() {
+        // Two columns without the header of a profile are a grid.
+        let heightmap = Heightmap::from_csv("0,10\n10,0\n5,5\n").unwrap();
+        assert_eq!(3, heightmap.height());
+        assert_eq!(Some(vec![0.5, 0.5]), heightmap.row(2));
 
-        paint.set_color(light_color);
-        canvas.draw_path(&path, paint);
+        // One elevation per line is a profile.
+        let heightmap = Heightmap::from_csv("elevation\n0\n5\n10\n").unwrap();
+        assert_eq!(1, heightmap.height());
+        assert_eq!(Some(vec![0.0, 0.5, 1.0]), heightmap.row(0));
     }
 }
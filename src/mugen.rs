@@ -1,26 +1,405 @@
+use std::f32::consts::PI;
+use std::fmt::Display;
+use std::str::FromStr;
+
 use skia_safe::{Canvas, Paint, PaintStyle, Path};
 
 use crate::utils::{Bounded, Palette};
 
+/// Points drawn for each half turn of the lemniscate, more for the curves with more details.
 const PRECISION: i32 = 128;
 const SCALE: f32 = 0.25;
+/// Points drawn at most, whatever the curve.
+const MAX_POINTS: usize = 200_000;
+/// Turns drawn of a curve that would not close with its parameters.
+const MAX_TURNS: f32 = 20.0;
+/// How far the red stroke is from the black one, in widths of the strokes.
+const RED_OFFSET: f32 = 0.1;
+/// How faded the swings of a harmonograph are when the drawing stops, as `exp(-FADING)`.
+const FADING: f32 = 5.0;
+/// How close the ends of a curve drawn to its size are when it closes.
+const CLOSING_DISTANCE: f32 = 1e-3;
+
+/// A parametric curve, with its parameters.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Curve {
+    /// The infinity symbol of the original painting.
+    #[default]
+    Lemniscate,
+    /// The sines of `a` and `b` times the angle, the first one shifted by `delta`.
+    Lissajous { a: f32, b: f32, delta: f32 },
+    /// Petals as many as `n / d`, or twice as many when one is even.
+    Rose { n: f32, d: f32 },
+    /// The path of a point of a circle rolling around a fixed one.
+    Epicycloid { fixed: f32, rolling: f32 },
+    /// The path of a point of a circle rolling inside a fixed one.
+    Hypocycloid { fixed: f32, rolling: f32 },
+    /// The path of a pen held at a distance from the center of a circle rolling inside a fixed
+    /// one.
+    Spirograph { fixed: f32, rolling: f32, pen: f32 },
+    /// Temple H. Fay's butterfly, drawn over a number of turns.
+    Butterfly { turns: f32 },
+    /// Johan Gielis' superformula, with `m` times a symmetry and the exponents `n1`, `n2` and
+    /// `n3` rounding or pinching it.
+    Superformula { m: f32, n1: f32, n2: f32, n3: f32 },
+    /// Two pendulums swinging along each axis at the frequencies `f1` and `f2`, `f3` and `f4`,
+    /// the first one of each axis shifted by `phase`, slowing down with `damping`.
+    Harmonograph {
+        f1: f32,
+        f2: f32,
+        f3: f32,
+        f4: f32,
+        phase: f32,
+        damping: f32,
+    },
+}
+
+impl Curve {
+    /// Every curve, with its default parameters.
+    pub const ALL: [Curve; 9] = [
+        Curve::Lemniscate,
+        Curve::Lissajous {
+            a: 3.0,
+            b: 2.0,
+            delta: PI / 2.0,
+        },
+        Curve::Rose { n: 5.0, d: 4.0 },
+        Curve::Epicycloid {
+            fixed: 5.0,
+            rolling: 2.0,
+        },
+        Curve::Hypocycloid {
+            fixed: 5.0,
+            rolling: 3.0,
+        },
+        Curve::Spirograph {
+            fixed: 5.0,
+            rolling: 3.0,
+            pen: 5.0,
+        },
+        Curve::Butterfly { turns: 6.0 },
+        Curve::Superformula {
+            m: 6.0,
+            n1: 1.0,
+            n2: 7.0,
+            n3: 8.0,
+        },
+        Curve::Harmonograph {
+            f1: 2.01,
+            f2: 3.0,
+            f3: 3.0,
+            f4: 2.0,
+            phase: PI / 2.0,
+            damping: 0.02,
+        },
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Curve::Lemniscate => "lemniscate",
+            Curve::Lissajous { .. } => "lissajous",
+            Curve::Rose { .. } => "rose",
+            Curve::Epicycloid { .. } => "epicycloid",
+            Curve::Hypocycloid { .. } => "hypocycloid",
+            Curve::Spirograph { .. } => "spirograph",
+            Curve::Butterfly { .. } => "butterfly",
+            Curve::Superformula { .. } => "superformula",
+            Curve::Harmonograph { .. } => "harmonograph",
+        }
+    }
+
+    /// The names and values of the parameters.
+    pub fn parameters(&self) -> Vec<(&'static str, f32)> {
+        let mut curve = *self;
+        curve
+            .parameters_mut()
+            .into_iter()
+            .map(|(name, value)| (name, *value))
+            .collect()
+    }
+
+    fn parameters_mut(&mut self) -> Vec<(&'static str, &mut f32)> {
+        match self {
+            Curve::Lemniscate => vec![],
+            Curve::Lissajous { a, b, delta } => vec![("a", a), ("b", b), ("delta", delta)],
+            Curve::Rose { n, d } => vec![("n", n), ("d", d)],
+            Curve::Epicycloid { fixed, rolling } | Curve::Hypocycloid { fixed, rolling } => {
+                vec![("fixed", fixed), ("rolling", rolling)]
+            }
+            Curve::Spirograph {
+                fixed,
+                rolling,
+                pen,
+            } => vec![("fixed", fixed), ("rolling", rolling), ("pen", pen)],
+            Curve::Butterfly { turns } => vec![("turns", turns)],
+            Curve::Superformula { m, n1, n2, n3 } => {
+                vec![("m", m), ("n1", n1), ("n2", n2), ("n3", n3)]
+            }
+            Curve::Harmonograph {
+                f1,
+                f2,
+                f3,
+                f4,
+                phase,
+                damping,
+            } => vec![
+                ("f1", f1),
+                ("f2", f2),
+                ("f3", f3),
+                ("f4", f4),
+                ("phase", phase),
+                ("damping", damping),
+            ],
+        }
+    }
+
+    /// Changes a parameter of the curve, the values that would stop it from being drawn being
+    /// refused.
+    pub fn set(&mut self, parameter: &str, value: f32) -> Result<(), String> {
+        let name = self.name();
+        let mut parameters = self.parameters_mut();
+        if parameters.is_empty() {
+            return Err(format!("ERROR: the {} curve has no parameters", name));
+        }
+        let names: Vec<&str> = parameters.iter().map(|(name, _)| *name).collect();
+        let current = parameters
+            .iter_mut()
+            .find(|(name, _)| *name == parameter)
+            .map(|(_, value)| value)
+            .ok_or_else(|| {
+                format!(
+                    "ERROR: unknown parameter `{}` of the {} curve, expected one of {}",
+                    parameter,
+                    name,
+                    names.join(", ")
+                )
+            })?;
+
+        // Dividing by them, or drawing for as long as they are slow.
+        let is_divisor = matches!(parameter, "d" | "rolling" | "turns" | "n1" | "damping");
+        if !value.is_finite() || (is_divisor && value <= 0.0) {
+            return Err(format!(
+                "ERROR: `{}` is not a valid {} of the {} curve",
+                value, parameter, name
+            ));
+        }
+        **current = value;
+
+        Ok(())
+    }
+
+    /// The point of the curve at `t`.
+    fn point(&self, t: f32) -> (f32, f32) {
+        match *self {
+            // Following a formula found here: https://gamedev.stackexchange.com/a/43704/143738
+            Curve::Lemniscate => {
+                let scale = 2.0 / (3.0 - (2.0 * t).cos());
+                (scale * t.cos(), scale * (2.0 * t).sin() / 2.0)
+            }
+            Curve::Lissajous { a, b, delta } => ((a * t + delta).sin(), (b * t).sin()),
+            Curve::Rose { n, d } => {
+                let radius = (n / d * t).cos();
+                (radius * t.cos(), radius * t.sin())
+            }
+            Curve::Epicycloid { fixed, rolling } => {
+                let ratio = (fixed + rolling) / rolling;
+                (
+                    (fixed + rolling) * t.cos() - rolling * (ratio * t).cos(),
+                    (fixed + rolling) * t.sin() - rolling * (ratio * t).sin(),
+                )
+            }
+            Curve::Hypocycloid { fixed, rolling } => Curve::Spirograph {
+                fixed,
+                rolling,
+                pen: rolling,
+            }
+            .point(t),
+            Curve::Spirograph {
+                fixed,
+                rolling,
+                pen,
+            } => {
+                let ratio = (fixed - rolling) / rolling;
+                (
+                    (fixed - rolling) * t.cos() + pen * (ratio * t).cos(),
+                    (fixed - rolling) * t.sin() - pen * (ratio * t).sin(),
+                )
+            }
+            Curve::Butterfly { .. } => {
+                let radius =
+                    t.sin().exp() - 2.0 * (4.0 * t).cos() + ((2.0 * t - PI) / 24.0).sin().powi(5);
+                (radius * t.sin(), -radius * t.cos())
+            }
+            Curve::Superformula { m, n1, n2, n3 } => {
+                let angle = m * t / 4.0;
+                let radius =
+                    (angle.cos().abs().powf(n2) + angle.sin().abs().powf(n3)).powf(-1.0 / n1);
+                (radius * t.cos(), radius * t.sin())
+            }
+            Curve::Harmonograph {
+                f1,
+                f2,
+                f3,
+                f4,
+                phase,
+                damping,
+            } => {
+                let fading = (-damping * t).exp();
+                (
+                    fading * ((f1 * t + phase).sin() + (f2 * t).sin()),
+                    fading * ((f3 * t).sin() + (f4 * t + phase).sin()),
+                )
+            }
+        }
+    }
+
+    /// The values of `t` the curve is drawn between, once around when it closes.
+    fn range(&self) -> (f32, f32) {
+        let turns = match *self {
+            Curve::Lemniscate => return (-PI, PI),
+            Curve::Lissajous { a, b, .. } => turns(a, 1.0).max(turns(b, 1.0)),
+            Curve::Rose { n, d } => turns(n, d),
+            Curve::Epicycloid { fixed, rolling }
+            | Curve::Hypocycloid { fixed, rolling }
+            | Curve::Spirograph { fixed, rolling, .. } => turns(fixed, rolling),
+            Curve::Butterfly { turns } => turns,
+            // The absolute values repeat every half turn of `m * t / 4`.
+            Curve::Superformula { m, .. } => turns(m, 2.0),
+            Curve::Harmonograph { damping, .. } => return (0.0, FADING / damping),
+        };
+        (0.0, turns * 2.0 * PI)
+    }
+
+    /// How many more points than the lemniscate the curve needs for a turn, as it goes around
+    /// faster.
+    fn detail(&self) -> f32 {
+        let detail = match *self {
+            Curve::Lemniscate => 1.0,
+            Curve::Lissajous { a, b, .. } => a.abs().max(b.abs()),
+            Curve::Rose { n, d } => n / d,
+            Curve::Epicycloid { fixed, rolling } => (fixed + rolling) / rolling,
+            Curve::Hypocycloid { fixed, rolling } | Curve::Spirograph { fixed, rolling, .. } => {
+                (fixed - rolling).abs() / rolling
+            }
+            Curve::Butterfly { .. } => 4.0,
+            Curve::Superformula { m, n1, n2, n3 } => m.abs() * n1.max(n2).max(n3),
+            Curve::Harmonograph { f1, f2, f3, f4, .. } => f1.max(f2).max(f3).max(f4),
+        };
+        detail.max(1.0)
+    }
+
+    /// Width of the strokes on a curve drawn to its size, thinner for the curves going over
+    /// themselves more often.
+    fn stroke_width(&self) -> f32 {
+        match self {
+            Curve::Lemniscate => 0.1,
+            Curve::Lissajous { .. } | Curve::Rose { .. } | Curve::Hypocycloid { .. } => 0.05,
+            Curve::Epicycloid { .. } => 0.04,
+            Curve::Spirograph { .. } => 0.03,
+            Curve::Butterfly { .. } => 0.02,
+            Curve::Superformula { .. } => 0.06,
+            Curve::Harmonograph { .. } => 0.008,
+        }
+    }
+
+    /// The points of the curve, centered and scaled so its largest side goes from -1.0 to 1.0,
+    /// and whether the last one joins the first.
+    fn points(&self) -> (Vec<(f32, f32)>, bool) {
+        let (start, end) = self.range();
+        let count = ((end - start) / PI * PRECISION as f32 * self.detail()).round() as usize;
+        let count = count.clamp(2, MAX_POINTS);
+        let mut points: Vec<(f32, f32)> = (0..count)
+            .map(|step| self.point(start + (end - start) * step as f32 / count as f32))
+            .collect();
+        let last = self.point(end);
+        points.push(last);
+
+        let (mut left, mut top, mut right, mut bottom) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for (x, y) in points.iter() {
+            (left, top) = (left.min(*x), top.min(*y));
+            (right, bottom) = (right.max(*x), bottom.max(*y));
+        }
+        let (center_x, center_y) = ((left + right) / 2.0, (top + bottom) / 2.0);
+        let half_size = ((right - left) / 2.0)
+            .max((bottom - top) / 2.0)
+            .max(f32::EPSILON);
+        for (x, y) in points.iter_mut() {
+            (*x, *y) = ((*x - center_x) / half_size, (*y - center_y) / half_size);
+        }
+
+        let (first, last) = (points[0], points[count]);
+        let closes = (first.0 - last.0).hypot(first.1 - last.1) < CLOSING_DISTANCE;
+        if closes {
+            points.pop();
+        }
+        (points, closes)
+    }
+}
+
+impl Display for Curve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Curve {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Curve::ALL
+            .iter()
+            .find(|curve| curve.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Curve::ALL.iter().map(|c| c.name()).collect();
+                format!(
+                    "ERROR: unknown curve `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// How many turns a curve going around `a / b` times as fast as its angle takes to come back
+/// to where it started, when both are whole numbers.
+fn turns(a: f32, b: f32) -> f32 {
+    let whole = |value: f32| (value - value.round()).abs() < 1e-3 && value.round() != 0.0;
+    if !whole(a) || !whole(b) {
+        return MAX_TURNS;
+    }
+
+    let (a, b) = (a.round().abs() as u32, b.round().abs() as u32);
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    (b / x) as f32
+}
 
 pub fn draw(canvas: &mut Canvas) {
+    draw_curve(canvas, &Curve::default());
+}
+
+/// Draws a curve as in the original painting: a black stroke, over a red one slightly offset.
+pub fn draw_curve(canvas: &mut Canvas, curve: &Curve) {
     canvas.clear(Palette::BEIGE);
 
+    let stroke_width = curve.stroke_width();
     let mut paint = Paint::default();
     paint.set_anti_alias(true);
     paint.set_style(PaintStyle::Stroke);
-    paint.set_stroke_width(0.1);
+    paint.set_stroke_width(stroke_width);
 
+    let (points, closes) = curve.points();
     let mut path = Path::new();
-    path.move_to(get_coords(-std::f32::consts::PI));
-
-    for step in (-PRECISION + 1)..PRECISION {
-        let step = step as f32 / PRECISION as f32 * std::f32::consts::PI;
-        path.line_to(get_coords(step));
+    path.move_to(points[0]);
+    for point in points[1..].iter() {
+        path.line_to(*point);
+    }
+    if closes {
+        path.close();
     }
-    path.close();
 
     canvas.save();
 
@@ -30,7 +409,8 @@ pub fn draw(canvas: &mut Canvas) {
     canvas.scale((scale, scale));
 
     canvas.save();
-    canvas.translate((0.01, 0.01));
+    let offset = stroke_width * RED_OFFSET;
+    canvas.translate((offset, offset));
     paint.set_color(Palette::RED);
     canvas.draw_path(&path, &paint);
     canvas.restore();
@@ -41,12 +421,35 @@ pub fn draw(canvas: &mut Canvas) {
     canvas.restore();
 }
 
-/// Get the coordinate of a point of the symbol, following a formula found
-/// here: https://gamedev.stackexchange.com/a/43704/143738
-fn get_coords(step: f32) -> (f32, f32) {
-    let scale = 2.0 / (3.0 - (2.0_f32 * step).cos());
-    let x = scale * step.cos();
-    let y = scale * (2.0_f32 * step).sin() / 2.0;
+#[cfg(test)]
+mod test {
+    use crate::mugen::*;
+
+    #[test]
+    fn draws_every_curve_to_its_size() {
+        for curve in Curve::ALL {
+            assert_eq!(curve.name().parse::<Curve>().unwrap(), curve);
+
+            let (points, closes) = curve.points();
+            assert!(points
+                .iter()
+                .all(|(x, y)| x.abs() <= 1.0 + 1e-5 && y.abs() <= 1.0 + 1e-5));
+            // Only the harmonograph, slowing down, doesn't come back to where it started.
+            let is_open = matches!(curve, Curve::Harmonograph { .. });
+            assert_eq!(closes, !is_open, "{}", curve);
+        }
 
-    (x, y)
+        // The lemniscate of the original painting fits the same size as before.
+        let (points, _) = Curve::Lemniscate.points();
+        assert_eq!(points.len(), PRECISION as usize * 2);
+        assert!((points[0].0 + 1.0).abs() < 1e-5);
+
+        let mut rose = "rose".parse::<Curve>().unwrap();
+        rose.set("n", 7.0).unwrap();
+        assert_eq!(rose.parameters(), vec![("n", 7.0), ("d", 4.0)]);
+        assert!(rose.set("d", 0.0).is_err());
+        assert!(rose.set("petals", 3.0).is_err());
+        assert!(Curve::Lemniscate.set("a", 1.0).is_err());
+        assert!("circle".parse::<Curve>().is_err());
+    }
 }
@@ -0,0 +1,128 @@
+use skia_safe::{Canvas, Color, Paint, Rect, Surface};
+
+use crate::utils::{Bounded, Palette};
+
+/// A lake at the bottom of a painting, reflecting whatever is drawn above it.
+#[derive(Clone, Debug)]
+pub struct Lake {
+    /// Fraction of the canvas height covered by the water, at most half.
+    pub depth: f32,
+    /// Color drawn over the reflection, its alpha being the strength of the tint.
+    pub tint: Color,
+    /// Horizontal shift of the furthest ripples, as a fraction of the canvas width.
+    pub ripple_amplitude: f32,
+    /// Height of a ripple, as a fraction of the canvas height.
+    pub ripple_height: f32,
+}
+
+impl Default for Lake {
+    fn default() -> Self {
+        Lake {
+            depth: 0.3,
+            tint: Palette::DARKER_BEIGE.with_a(0x80),
+            ripple_amplitude: 0.004,
+            ripple_height: 0.004,
+        }
+    }
+}
+
+impl Lake {
+    /// Height of the water line. The depth is clamped to half the canvas, as deeper water would
+    /// leave the bottom of the lake without anything to reflect.
+    fn horizon(&self, height: f32) -> f32 {
+        (height * (1.0 - self.depth.clamp(0.0, 0.5))).round()
+    }
+
+    /// The strips of the reflection from the water line down, each with its horizontal shift.
+    fn ripples(&self, width: f32, height: f32) -> Vec<(Rect, f32)> {
+        let horizon = self.horizon(height);
+        let ripple_height = (self.ripple_height * height).max(1.0);
+        let mut ripples = Vec::new();
+        let mut top = horizon;
+        while top < height {
+            // Ripples get wider close to the viewer.
+            let distance = (top - horizon) / (height - horizon);
+            let index = ripples.len() as f32;
+            let shift = (index * 1.7).sin()
+                * (index * 0.3).cos()
+                * self.ripple_amplitude
+                * width
+                * (0.5 + distance);
+
+            ripples.push((
+                Rect::new(0.0, top, width, (top + ripple_height).min(height)),
+                shift,
+            ));
+            top += ripple_height;
+        }
+        ripples
+    }
+
+    /// Draws the painting above the water line, then its mirrored, tinted and rippled
+    /// reflection below. The painting is drawn once on its own surface, so it is free to clear
+    /// the canvas or replace the matrix.
+    pub fn draw_reflected<F>(&self, canvas: &mut Canvas, draw_fn: F)
+    where
+        F: FnOnce(&mut Canvas),
+    {
+        let width = canvas.width();
+        let height = canvas.height();
+        let horizon = self.horizon(height);
+
+        let mut surface = Surface::new_raster_n32_premul((width as i32, horizon as i32))
+            .expect("No SKIA surface available.");
+        draw_fn(surface.canvas());
+        let image = surface.image_snapshot();
+
+        canvas.draw_image(&image, (0.0, 0.0), None);
+
+        // The water is filled with the plain reflection first, so that the edges uncovered by
+        // the shifted strips do not show whatever was below.
+        let water = Rect::new(0.0, horizon, width, height);
+        let mut ripples = vec![(water, 0.0)];
+        ripples.extend(self.ripples(width, height));
+        for (strip, shift) in ripples {
+            canvas.save();
+            canvas.clip_rect(strip, None, None);
+            canvas.translate((shift, horizon * 2.0));
+            canvas.scale((1.0, -1.0));
+            canvas.draw_image(&image, (0.0, 0.0), None);
+            canvas.restore();
+        }
+
+        let mut paint = Paint::default();
+        paint.set_color(self.tint);
+        canvas.draw_rect(water, &paint);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reflection::*;
+
+    #[test]
+    fn ripples_cover_the_water() {
+        let lake = Lake::default();
+        assert_eq!(70.0, lake.horizon(100.0));
+
+        let ripples = lake.ripples(200.0, 100.0);
+        assert_eq!(70.0, ripples[0].0.top);
+        assert_eq!(100.0, ripples[ripples.len() - 1].0.bottom);
+        assert!(ripples
+            .windows(2)
+            .all(|pair| pair[0].0.bottom == pair[1].0.top));
+        // The closest ripples shift at most one and a half times the amplitude.
+        assert!(ripples
+            .iter()
+            .all(|(_, shift)| shift.abs() <= 0.004 * 200.0 * 1.5));
+
+        // Deep water is capped to half the canvas, so that the whole lake has a reflection.
+        for depth in [0.5, 1.0, 2.0] {
+            let lake = Lake {
+                depth,
+                ..Lake::default()
+            };
+            assert_eq!(50.0, lake.horizon(100.0));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::io::Read;
+use std::process::{Command, Stdio};
+
+/// A key pressed in the terminal.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Char(char),
+}
+
+/// The terminal in raw mode, keys being read as soon as they are pressed and without being
+/// printed, until it is dropped. Relies on `stty`, so only works on Unix terminals.
+pub struct RawTerminal {
+    /// The settings of the terminal before, to restore them.
+    settings: String,
+}
+
+impl RawTerminal {
+    pub fn new() -> Result<Self, String> {
+        let settings = stty(&["-g"])?;
+        // Reads return after a tenth of a second without keys, so the screen can still update.
+        stty(&["raw", "-echo", "min", "0", "time", "1"])?;
+
+        Ok(RawTerminal {
+            settings: settings.trim().to_string(),
+        })
+    }
+
+    /// The number of columns and rows of the terminal.
+    pub fn size(&self) -> Result<(usize, usize), String> {
+        let size = stty(&["size"])?;
+        match size.split_whitespace().collect::<Vec<&str>>()[..] {
+            [rows, columns] => rows
+                .parse()
+                .ok()
+                .zip(columns.parse().ok())
+                .map(|(rows, columns)| (columns, rows))
+                .ok_or_else(|| format!("ERROR: unexpected terminal size `{}`", size.trim())),
+            _ => Err(format!("ERROR: unexpected terminal size `{}`", size.trim())),
+        }
+    }
+
+    /// The key pressed, if any was within a tenth of a second.
+    pub fn read_key(&self) -> Result<Option<Key>, String> {
+        let mut buffer = [0; 8];
+        let count = std::io::stdin()
+            .read(&mut buffer)
+            .map_err(|e| format!("ERROR: failed to read the keys: {}", e))?;
+
+        let key = match buffer[..count] {
+            [] => None,
+            [0x1b, b'[', b'A', ..] => Some(Key::Up),
+            [0x1b, b'[', b'B', ..] => Some(Key::Down),
+            [0x1b, b'[', b'C', ..] => Some(Key::Right),
+            [0x1b, b'[', b'D', ..] => Some(Key::Left),
+            _ => std::str::from_utf8(&buffer[..count])
+                .ok()
+                .and_then(|keys| keys.chars().next())
+                .map(Key::Char),
+        };
+        Ok(key)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        // Nothing more can be done if the settings can't be restored, `reset` still can.
+        let _ = stty(&[&self.settings]);
+    }
+}
+
+/// Runs `stty` on the terminal, returning what it printed.
+fn stty(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .map_err(|e| format!("ERROR: failed to run `stty`: {}", e))?;
+    if !output.status.success() {
+        return Err("ERROR: the maze can only be played in a terminal".to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -1,3 +1,4 @@
+use serde::Serialize;
 use skia_safe::{Canvas, Color, Paint, Rect, M44};
 
 use crate::utils::{Bounded, Palette};
@@ -7,7 +8,8 @@ const BASE_LENGTH: f32 = 10.0;
 const SKY_COLOR: Color = Palette::BEIGE;
 const TREE_AND_EARTH_COLOR: Color = Palette::BLACK;
 const ROOT_COLOR: Color = Palette::RED;
-const DEPTH: u32 = 10;
+const TREE_DEPTH: u32 = 10;
+const ROOT_DEPTH: u32 = (TREE_DEPTH * 3) / 4;
 const IDEAL_WIDTH: f32 = 1920.0;
 const IDEAL_HEIGHT: f32 = 1080.0;
 
@@ -18,42 +20,11 @@ pub fn draw(canvas: &mut Canvas) {
     let width = IDEAL_WIDTH;
     let height = IDEAL_HEIGHT;
 
-    let tree_depth = DEPTH;
-    let root_depth = (DEPTH * 3) / 4;
-
     // Fill with the sky color.
     canvas.clear(SKY_COLOR);
 
-    // Calculate how big the tree and roots will be, so we can then draw them at the proper space.
-    let mut tree_rect = Rect::new(0.0, 0.0, 0.0, 0.0);
-    let mut root_rect = Rect::new(0.0, 0.0, 0.0, 0.0);
-
-    let mut calc_tree = |x1: f32, y1: f32, x2: f32, y2: f32, _depth: u32, rect: &mut Rect| {
-        bound_branch(x1, y1, x2, y2, rect);
-    };
-    parse_fractal_tree(
-        0.0,
-        0.0,
-        0.0,
-        tree_depth,
-        BASE_LENGTH,
-        &mut tree_rect,
-        &mut calc_tree,
-    );
-
-    parse_fractal_tree(
-        0.0,
-        0.0,
-        0.0,
-        root_depth,
-        BASE_LENGTH * 0.75,
-        &mut root_rect,
-        &mut calc_tree,
-    );
-
     // Set the center of the tree, and earth level, so that the drawing will be perfectly centered.
-    let tree_trunk_x = width / 2.0 - tree_rect.center_x();
-    let earth_level = (height + tree_rect.height() - root_rect.height()) / 2.0;
+    let (tree_trunk_x, earth_level) = layout(width, height);
 
     let scale = canvas.height() / height;
     let shift_x = -(width * scale - canvas.width()) / 2.0;
@@ -77,7 +48,7 @@ pub fn draw(canvas: &mut Canvas) {
     // Draw the upper tree.
     paint.set_color(TREE_AND_EARTH_COLOR);
     let mut draw = |x1: f32, y1: f32, x2: f32, y2: f32, depth: u32, paint: &mut Paint| {
-        paint.set_stroke_width((depth as f32).powf(1.1));
+        paint.set_stroke_width(branch_thickness(depth));
         let first = (x1 as f32, y1 as f32);
         let second = (x2 as f32, y2 as f32);
         canvas.draw_line(first, second, paint);
@@ -87,7 +58,7 @@ pub fn draw(canvas: &mut Canvas) {
         tree_trunk_x,
         earth_level,
         0.0,
-        tree_depth,
+        TREE_DEPTH,
         BASE_LENGTH,
         &mut paint,
         &mut draw,
@@ -100,13 +71,192 @@ pub fn draw(canvas: &mut Canvas) {
         tree_trunk_x,
         earth_level,
         180.0,
-        root_depth,
+        ROOT_DEPTH,
         BASE_LENGTH * 0.75,
         &mut paint,
         &mut draw,
     );
 }
 
+/// Which part of the painting a branch belongs to.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Part {
+    Tree,
+    Root,
+}
+
+impl Part {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Part::Tree => "tree",
+            Part::Root => "root",
+        }
+    }
+}
+
+/// A single branch of the skeleton, expressed in the coordinates of the ideal painting size.
+#[derive(Debug, Serialize)]
+pub struct Branch {
+    pub part: Part,
+    pub start: (f32, f32),
+    pub end: (f32, f32),
+    pub depth: u32,
+    /// Index of the parent branch in the skeleton, `None` for the trunk and the main root.
+    pub parent: Option<usize>,
+    pub thickness: f32,
+}
+
+/// The branch hierarchy of the tree and its roots, as drawn by [`draw`], for other renderers to
+/// consume.
+#[derive(Debug, Serialize)]
+pub struct Skeleton {
+    pub width: f32,
+    pub height: f32,
+    pub branches: Vec<Branch>,
+}
+
+impl Skeleton {
+    pub fn from_painting() -> Self {
+        let width = IDEAL_WIDTH;
+        let height = IDEAL_HEIGHT;
+        let (tree_trunk_x, earth_level) = layout(width, height);
+
+        let mut branches = Vec::new();
+        Skeleton::collect(
+            &mut branches,
+            Part::Tree,
+            tree_trunk_x,
+            earth_level,
+            0.0,
+            TREE_DEPTH,
+            BASE_LENGTH,
+        );
+        Skeleton::collect(
+            &mut branches,
+            Part::Root,
+            tree_trunk_x,
+            earth_level,
+            180.0,
+            ROOT_DEPTH,
+            BASE_LENGTH * 0.75,
+        );
+
+        Skeleton {
+            width,
+            height,
+            branches,
+        }
+    }
+
+    /// Walks a fractal tree and appends its branches. Branches are visited depth first, and the
+    /// depth decreases by one at each level, so the parent of a branch is always the last
+    /// visited branch with a depth of one more.
+    fn collect(
+        branches: &mut Vec<Branch>,
+        part: Part,
+        x: f32,
+        y: f32,
+        angle: f64,
+        depth: u32,
+        base_length: f32,
+    ) {
+        let mut last_at_depth: Vec<Option<usize>> = vec![None; depth as usize + 2];
+
+        let mut collect_branch =
+            |x1: f32, y1: f32, x2: f32, y2: f32, depth: u32, branches: &mut Vec<Branch>| {
+                let index = branches.len();
+                branches.push(Branch {
+                    part,
+                    start: (x1, y1),
+                    end: (x2, y2),
+                    depth,
+                    parent: last_at_depth[depth as usize + 1],
+                    thickness: branch_thickness(depth),
+                });
+                last_at_depth[depth as usize] = Some(index);
+            };
+
+        parse_fractal_tree(
+            x,
+            y,
+            angle,
+            depth,
+            base_length,
+            branches,
+            &mut collect_branch,
+        );
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("ERROR: couldn't serialize the skeleton: {}", e))
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("index,part,depth,parent,start_x,start_y,end_x,end_y,thickness\n");
+        for (index, branch) in self.branches.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                index,
+                branch.part.as_str(),
+                branch.depth,
+                branch
+                    .parent
+                    .map(|parent| parent.to_string())
+                    .unwrap_or_default(),
+                branch.start.0,
+                branch.start.1,
+                branch.end.0,
+                branch.end.1,
+                branch.thickness,
+            ));
+        }
+
+        csv
+    }
+}
+
+/// Calculate how big the tree and roots will be, and return the position of the trunk and the
+/// earth level so they can be drawn at the proper space.
+fn layout(width: f32, height: f32) -> (f32, f32) {
+    let mut tree_rect = Rect::new(0.0, 0.0, 0.0, 0.0);
+    let mut root_rect = Rect::new(0.0, 0.0, 0.0, 0.0);
+
+    let mut calc_tree = |x1: f32, y1: f32, x2: f32, y2: f32, _depth: u32, rect: &mut Rect| {
+        bound_branch(x1, y1, x2, y2, rect);
+    };
+    parse_fractal_tree(
+        0.0,
+        0.0,
+        0.0,
+        TREE_DEPTH,
+        BASE_LENGTH,
+        &mut tree_rect,
+        &mut calc_tree,
+    );
+
+    parse_fractal_tree(
+        0.0,
+        0.0,
+        0.0,
+        ROOT_DEPTH,
+        BASE_LENGTH * 0.75,
+        &mut root_rect,
+        &mut calc_tree,
+    );
+
+    let tree_trunk_x = width / 2.0 - tree_rect.center_x();
+    let earth_level = (height + tree_rect.height() - root_rect.height()) / 2.0;
+
+    (tree_trunk_x, earth_level)
+}
+
+fn branch_thickness(depth: u32) -> f32 {
+    (depth as f32).powf(1.1)
+}
+
 fn parse_fractal_tree<Block, Param>(
     x1: f32,
     y1: f32,
@@ -162,3 +312,52 @@ fn bound_branch(x1: f32, y1: f32, x2: f32, y2: f32, rect: &mut Rect) {
         rect.bottom
     };
 }
+
+#[cfg(test)]
+mod test {
+    use crate::tree::*;
+
+    #[test]
+    fn skeleton_has_every_branch() {
+        let skeleton = Skeleton::from_painting();
+        let tree_count = 2usize.pow(TREE_DEPTH + 1) - 1;
+        let root_count = 2usize.pow(ROOT_DEPTH + 1) - 1;
+
+        assert_eq!(tree_count + root_count, skeleton.branches.len());
+    }
+
+    #[test]
+    fn skeleton_children_start_at_parent_end() {
+        let skeleton = Skeleton::from_painting();
+
+        for branch in skeleton.branches.iter() {
+            match branch.parent {
+                Some(parent) => {
+                    let parent = &skeleton.branches[parent];
+                    assert_eq!(parent.part, branch.part);
+                    assert_eq!(parent.depth, branch.depth + 1);
+                    assert_eq!(parent.end, branch.start);
+                }
+                None => {
+                    let depth = match branch.part {
+                        Part::Tree => TREE_DEPTH,
+                        Part::Root => ROOT_DEPTH,
+                    };
+                    assert_eq!(depth, branch.depth);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn skeleton_to_csv() {
+        let csv = Skeleton::from_painting().to_csv();
+        let mut lines = csv.lines();
+
+        assert_eq!(
+            Some("index,part,depth,parent,start_x,start_y,end_x,end_y,thickness"),
+            lines.next()
+        );
+        assert!(lines.next().unwrap().starts_with("0,tree,10,,"));
+    }
+}
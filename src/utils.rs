@@ -24,6 +24,19 @@ pub trait Drawable {
     }
 }
 
+/// Linear interpolation between two colors, `ratio` going from 0.0 (`from`) to 1.0 (`to`).
+pub fn mix(from: Color, to: Color, ratio: f32) -> Color {
+    let ratio = ratio.clamp(0.0, 1.0);
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * ratio).round() as u8;
+
+    Color::from_argb(
+        channel(from.a(), to.a()),
+        channel(from.r(), to.r()),
+        channel(from.g(), to.g()),
+        channel(from.b(), to.b()),
+    )
+}
+
 pub struct Palette;
 
 impl Palette {